@@ -0,0 +1,46 @@
+use std::process::Stdio;
+
+use super::list_apps::InstalledApp;
+use super::{Device, Execute, Result, Validate};
+
+impl Device {
+    /// Returns information about the app with the given bundle ID that is
+    /// installed on this device. This is more efficient than
+    /// [`Device::list_apps`] if you already know the bundle ID.
+    pub fn app_info(&self, bundle_id: &str) -> Result<InstalledApp> {
+        let output = self
+            .simctl()
+            .command("appinfo")
+            .arg(&self.udid)
+            .arg(bundle_id)
+            .stdout(Stdio::piped())
+            .execute(self.simctl())?;
+
+        let output = output.validate_with_output()?;
+
+        Ok(plist::from_reader_ascii(&output.stdout[..])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::list_apps::ApplicationType;
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    fn test_app_info() -> Result<()> {
+        mock::device()?.boot()?;
+
+        let safari = mock::device()?.app_info("com.apple.mobilesafari")?;
+        assert_eq!(safari.bundle_id, "com.apple.mobilesafari");
+        assert_eq!(safari.application_type, ApplicationType::System);
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+}
@@ -1,7 +1,69 @@
-use std::ffi::OsStr;
+//! Supporting types for the `simctl boot` subcommand.
+
+use std::ffi::{OsStr, OsString};
 use std::fmt::Display;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use super::launch::Arch;
+use super::list::DeviceState;
+#[cfg(feature = "tokio")]
+use super::ExecuteAsync;
+use super::{Device, Error, Execute, Result, Simctl, Validate};
+
+/// Builder that can be used to customize the boot of a device (see
+/// [`Device::boot_with_options`]).
+#[derive(Clone, Debug, Default)]
+pub struct BootOptions {
+    arch: Option<Arch>,
+    envs: Vec<(String, OsString)>,
+}
+
+impl BootOptions {
+    /// Returns a new builder with the default options (i.e. no environment
+    /// variables and the native architecture).
+    pub fn new() -> BootOptions {
+        BootOptions::default()
+    }
+
+    /// Boots the device as the given architecture (e.g. to run apps with
+    /// Intel-only dependencies on Apple Silicon). This is only supported by
+    /// recent versions of Xcode.
+    pub fn arch(&mut self, arch: Arch) -> &mut BootOptions {
+        self.arch = Some(arch);
+        self
+    }
+
+    /// Adds an environment variable that will be made available to the
+    /// processes of the device. Do not prepend `SIMCTL_CHILD_` to the variable
+    /// name: this is done automatically.
+    pub fn env<K, V>(&mut self, key: K, value: V) -> &mut BootOptions
+    where
+        K: Display,
+        V: AsRef<OsStr>,
+    {
+        self.envs.push((
+            format!("SIMCTL_CHILD_{}", key),
+            value.as_ref().to_os_string(),
+        ));
+        self
+    }
 
-use super::{Device, Result, Validate};
+    /// Adds each of the given environment variables (see [`BootOptions::env`]).
+    pub fn envs<I, K, V>(&mut self, envs: I) -> &mut BootOptions
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Display,
+        V: AsRef<OsStr>,
+    {
+        for (key, value) in envs {
+            self.env(key, value);
+        }
+
+        self
+    }
+}
 
 impl Device {
     /// Boots this device. If the device is already booted, this function will
@@ -10,7 +72,29 @@ impl Device {
     /// NOTE: this does not automatically open the visual simulator interface.
     /// Use [`crate::Simctl::open()`] to open the visual interface.
     pub fn boot(&self) -> Result<()> {
-        self.boot_with_env(Vec::<(String, &OsStr)>::new())
+        self.boot_with_options(&BootOptions::new())
+    }
+
+    /// Boots this device unless it is already booted. In contrast to
+    /// [`Device::boot`], this function does not return an error if the device
+    /// is already booted. The state of the device is refreshed first, so this
+    /// also works if the device was booted after this instance was retrieved.
+    /// If the device is still booting, this waits until it has finished
+    /// booting (see [`Device::wait_for_boot`]) rather than returning an error.
+    pub fn boot_if_needed(&self) -> Result<()> {
+        match self.current_state()? {
+            DeviceState::Booted => Ok(()),
+            state if state >= DeviceState::Booting => self.wait_for_boot(),
+            _ => self.boot(),
+        }
+    }
+
+    /// Boots this device unless it is already booted (see
+    /// [`Device::boot_if_needed`]) and subsequently opens the visual simulator
+    /// interface, focused on this device (see [`Device::show`]).
+    pub fn boot_and_open(&self) -> Result<()> {
+        self.boot_if_needed()?;
+        self.show()
     }
 
     /// Boots this device with the given environment variables. Do not prepend
@@ -26,25 +110,97 @@ impl Device {
         K: Display,
         V: AsRef<OsStr>,
     {
+        self.boot_with_options(BootOptions::new().envs(envs))
+    }
+
+    /// Boots this device with the given options. If the device is already
+    /// booted, this function will return an error (as does the underlying
+    /// CLI).
+    ///
+    /// NOTE: this does not automatically open the visual simulator interface.
+    /// Use [`crate::Simctl::open()`] to open the visual interface.
+    pub fn boot_with_options(&self, options: &BootOptions) -> Result<()> {
+        let mut command = self.simctl().command("boot");
+        command.arg(&self.info().udid);
+
+        if let Some(arch) = options.arch {
+            command.arg(format!("--arch={}", arch.as_arg()));
+        }
+
+        command
+            .envs(options.envs.iter().map(|(key, value)| (key, value)))
+            .execute(self.simctl())?
+            .validate()
+    }
+
+    /// Asynchronous variant of [`Device::boot`].
+    #[cfg(feature = "tokio")]
+    pub async fn boot_async(&self) -> Result<()> {
         self.simctl()
-            .command("boot")
+            .command_async("boot")
             .arg(&self.info().udid)
-            .envs(
-                envs.into_iter()
-                    .map(|(key, value)| (format!("SIMCTL_CHILD_{}", key), value)),
-            )
-            .output()?
+            .execute_async(self.simctl())
+            .await?
             .validate()
     }
 }
 
+impl Simctl {
+    /// Boots each of the given devices, running at most the given number of
+    /// boots concurrently to avoid overwhelming the CoreSimulator service.
+    /// Devices that fail to boot don't prevent the others from booting. If
+    /// any device fails, [`Error::Multiple`] is returned with the error of
+    /// each of those devices (in the order of the given devices).
+    pub fn boot_all(&self, devices: &[&Device], max_concurrency: usize) -> Result<()> {
+        if max_concurrency == 0 {
+            return Err(Error::InvalidArgument(
+                "max concurrency must be at least 1".to_owned(),
+            ));
+        }
+
+        let next = AtomicUsize::new(0);
+        let errors = Mutex::new(vec![]);
+
+        thread::scope(|scope| {
+            for _ in 0..max_concurrency.min(devices.len()) {
+                scope.spawn(|| {
+                    while let Some(device) = devices.get(next.fetch_add(1, Ordering::SeqCst)) {
+                        if let Err(error) = device.boot() {
+                            let mut errors = match errors.lock() {
+                                Ok(errors) => errors,
+                                Err(error) => error.into_inner(),
+                            };
+
+                            errors.push((device.udid.clone(), error));
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut errors = match errors.into_inner() {
+            Ok(errors) => errors,
+            Err(error) => error.into_inner(),
+        };
+
+        if errors.is_empty() {
+            return Ok(());
+        }
+
+        errors.sort_by_key(|(udid, _)| devices.iter().position(|device| &device.udid == udid));
+
+        Err(Error::Multiple(errors))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serial_test::serial;
 
+    use std::path::Path;
+
     use super::*;
-    use crate::list::DeviceState;
-    use crate::mock;
+    use crate::{mock, DeviceQuery};
 
     #[test]
     #[serial]
@@ -57,4 +213,161 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_boot_with_options() -> Result<()> {
+        mock::device()?.boot_with_options(BootOptions::new().env("TEST_VAR", "Hello World!"))?;
+        assert_eq!(mock::device()?.getenv("TEST_VAR")?, "Hello World!");
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_boot_with_options_arch() -> Result<()> {
+        let simctl = mock::stub(
+            "boot_arch",
+            "[ \"$1 $2 $3\" = \"boot 2C7A3E5E-5C2C-4B37-9C1C-6F0A4C1A1E01 --arch=x86_64\" ]",
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        device.boot_with_options(BootOptions::new().arch(Arch::X86_64))?;
+        assert!(device.boot().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_boot_all() -> Result<()> {
+        let device = mock::device()?;
+        let list = device.simctl().list()?;
+        let other = list
+            .devices()
+            .iter()
+            .available()
+            .by_runtime(&device.runtime_identifier)
+            .find(|next| next.udid != device.udid && next.state == DeviceState::Shutdown)
+            .unwrap();
+
+        device.simctl().boot_all(&[&device, other], 2)?;
+        assert_eq!(device.current_state()?, DeviceState::Booted);
+        assert_eq!(other.current_state()?, DeviceState::Booted);
+
+        device.shutdown()?;
+        other.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_boot_all_errors() -> Result<()> {
+        let simctl = mock::stub(
+            "boot_all",
+            r#"case "$2" in 8F3D6B0C-*|E7A9C3D5-*) echo "Unable to boot $2" >&2; exit 1 ;; esac"#,
+        )?;
+        let list = mock::fixture_with(simctl.clone())?;
+        let mut devices = list.devices().iter().available().collect::<Vec<_>>();
+        devices.sort_by(|a, b| a.udid.cmp(&b.udid));
+
+        match simctl.boot_all(&devices, 2) {
+            Err(Error::Multiple(errors)) => assert_eq!(
+                errors
+                    .iter()
+                    .map(|(udid, error)| (udid.as_str(), error.simctl_message()))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (
+                        "8F3D6B0C-1E4A-4F7B-A2D5-3B9E7C6A2D02",
+                        Some("Unable to boot 8F3D6B0C-1E4A-4F7B-A2D5-3B9E7C6A2D02")
+                    ),
+                    (
+                        "E7A9C3D5-2B8F-4C1E-9A6D-4F2B7E1C8A05",
+                        Some("Unable to boot E7A9C3D5-2B8F-4C1E-9A6D-4F2B7E1C8A05")
+                    ),
+                ]
+            ),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let healthy = devices
+            .iter()
+            .find(|device| device.udid.starts_with("2C7A3E5E-"))
+            .unwrap();
+        assert!(simctl.boot_all(&[healthy], 4).is_ok());
+        assert!(matches!(
+            simctl.boot_all(&devices, 0),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_boot_if_needed() -> Result<()> {
+        mock::device()?.boot_if_needed()?;
+        mock::device()?.boot_if_needed()?;
+        assert_eq!(mock::device()?.state, DeviceState::Booted);
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_boot_if_needed_booting_stub() -> Result<()> {
+        let log = std::env::temp_dir().join("simctl_stub_boot_if_needed.log");
+        let _ = std::fs::remove_file(&log);
+
+        // The stub reports the first device (the iPhone SE) as booting and
+        // refuses to boot it again, like the CLI.
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/list.json");
+        let script = format!(
+            r#"echo "$1" >> '{}'
+case "$1" in
+list) awk '!done && /"state" : "Shutdown"/ {{ sub("Shutdown", "Booting"); done = 1 }} 1' '{}' ;;
+bootstatus) [ "$2 $3" = "2C7A3E5E-5C2C-4B37-9C1C-6F0A4C1A1E01 -b" ] ;;
+*) echo "Unable to boot device in current state: Booting" >&2; exit 149 ;;
+esac"#,
+            log.display(),
+            fixture.display()
+        );
+
+        let simctl = mock::stub("boot_if_needed", &script)?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        assert_eq!(device.current_state()?, DeviceState::Booting);
+        device.boot_if_needed()?;
+        assert_eq!(std::fs::read_to_string(&log)?, "list\nlist\nbootstatus\n");
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    #[ignore = "opens the Simulator.app GUI"]
+    fn test_boot_and_open() -> Result<()> {
+        mock::device()?.boot_and_open()?;
+        assert_eq!(mock::device()?.state, DeviceState::Booted);
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    #[serial]
+    async fn test_boot_async() -> Result<()> {
+        mock::device()?.boot_async().await?;
+        assert_eq!(mock::device()?.state, DeviceState::Booted);
+
+        mock::device()?.shutdown_async().await?;
+        assert_eq!(mock::device()?.state, DeviceState::Shutdown);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,44 @@
+use super::{Device, Execute, Result, Validate};
+
+impl Device {
+    /// Waits until this device has finished booting and all of its services
+    /// have launched. If the device isn't booted yet, it will be booted first.
+    /// Unlike [`Device::boot`], this function does not return an error if the
+    /// device is already booted.
+    ///
+    /// This is useful to avoid races with services (such as the screenshot
+    /// service used by [`crate::io::IO::screenshot`]) that are not yet
+    /// available immediately after [`Device::boot`] returns.
+    pub fn wait_for_boot(&self) -> Result<()> {
+        self.simctl()
+            .command("bootstatus")
+            .arg(&self.udid)
+            .arg("-b")
+            .execute(self.simctl())?
+            .validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::io::{Display, ImageType, Mask};
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    fn test_wait_for_boot() -> Result<()> {
+        mock::device()?.boot()?;
+        mock::device()?.wait_for_boot()?;
+
+        mock::device()?
+            .io()
+            .screenshot(ImageType::Png, Display::Internal, Mask::Ignored)?;
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+}
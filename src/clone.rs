@@ -0,0 +1,112 @@
+use std::process::Stdio;
+
+use super::{Device, Error, Execute, Result, Validate};
+
+impl Device {
+    /// Clones this device (including its apps and data) into a new device with
+    /// the given name that runs the given runtime (see
+    /// [`crate::list::Runtime::identifier`]) and returns the new device.
+    ///
+    /// The CLI cannot clone into a different runtime directly. Instead, the
+    /// clone is subsequently upgraded to the given runtime. Therefore, the
+    /// runtime must be the same as or newer than the runtime of this device
+    /// (and target the same platform). If the upgrade fails, the clone is
+    /// deleted (on a best-effort basis) before the error is returned.
+    pub fn clone_to_runtime(&self, name: &str, runtime_id: &str) -> Result<Device> {
+        let output = self
+            .simctl()
+            .command("clone")
+            .arg(&self.udid)
+            .arg(name)
+            .stdout(Stdio::piped())
+            .execute(self.simctl())?;
+
+        let output = output.validate_with_output()?;
+
+        // The CLI writes the UDID of the new device to stdout.
+        let udid = String::from_utf8(output.stdout)?.trim().to_owned();
+
+        if runtime_id != self.runtime_identifier {
+            let result = self
+                .simctl()
+                .command("upgrade")
+                .arg(&udid)
+                .arg(runtime_id)
+                .execute(self.simctl())
+                .and_then(|output| output.validate());
+
+            if let Err(error) = result {
+                // Don't leave a half-finished clone behind. The error of the
+                // upgrade is more useful than that of the cleanup.
+                let _ = self
+                    .simctl()
+                    .command("delete")
+                    .arg(&udid)
+                    .execute(self.simctl());
+
+                return Err(error);
+            }
+        }
+
+        self.simctl()
+            .list()?
+            .into_device(&udid)
+            .ok_or_else(|| Error::device_not_found(&udid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    fn test_clone_to_runtime() -> Result<()> {
+        let device = mock::device()?;
+
+        let clone = device.clone_to_runtime("simctl clone", &device.runtime_identifier)?;
+        assert_ne!(clone.udid, device.udid);
+        assert_eq!(clone.name, "simctl clone");
+        assert_eq!(clone.runtime_identifier, device.runtime_identifier);
+
+        device.simctl().raw("delete", &[&clone.udid])?;
+
+        Ok(())
+    }
+    #[test]
+    fn test_clone_to_runtime_upgrade_failure_stub() -> Result<()> {
+        let log = std::env::temp_dir().join("simctl_stub_clone_upgrade.log");
+        let _ = std::fs::remove_file(&log);
+
+        let script = format!(
+            r#"echo "$@" >> '{}'
+case "$1" in
+clone) echo "9B0E2F4A-1D3C-4E5F-8A6B-7C8D9E0F1A2B" ;;
+upgrade) echo "Unable to upgrade device" >&2; exit 1 ;;
+esac"#,
+            log.display()
+        );
+
+        let simctl = mock::stub("clone_upgrade", &script)?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        let error = device
+            .clone_to_runtime(
+                "simctl clone",
+                "com.apple.CoreSimulator.SimRuntime.iOS-15-0",
+            )
+            .unwrap_err();
+        assert_eq!(error.simctl_message(), Some("Unable to upgrade device"));
+        assert_eq!(
+            std::fs::read_to_string(&log)?,
+            "clone 2C7A3E5E-5C2C-4B37-9C1C-6F0A4C1A1E01 simctl clone\n\
+             upgrade 9B0E2F4A-1D3C-4E5F-8A6B-7C8D9E0F1A2B com.apple.CoreSimulator.SimRuntime.iOS-15-0\n\
+             delete 9B0E2F4A-1D3C-4E5F-8A6B-7C8D9E0F1A2B\n"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use std::process::Stdio;
+
+use super::{Device, Error, Execute, Result, Simctl, Validate};
+
+impl Simctl {
+    /// Creates a new device with the given name, device type (see
+    /// [`crate::list::DeviceType::identifier`]) and runtime (see
+    /// [`crate::list::Runtime::identifier`]) and returns the new device.
+    pub fn create(&self, name: &str, device_type_id: &str, runtime_id: &str) -> Result<Device> {
+        let output = self
+            .command("create")
+            .arg(name)
+            .arg(device_type_id)
+            .arg(runtime_id)
+            .stdout(Stdio::piped())
+            .execute(self)?;
+
+        let output = output.validate_with_output()?;
+
+        // The CLI writes the UDID of the new device to stdout.
+        let udid = String::from_utf8(output.stdout)?.trim().to_owned();
+
+        self.list()?
+            .into_device(&udid)
+            .ok_or_else(|| Error::device_not_found(&udid))
+    }
+
+    /// Returns the first available device with the given name, device type and
+    /// runtime, or creates one if there is no such device yet (see
+    /// [`Simctl::create`]). This makes it possible to set up a device
+    /// idempotently.
+    pub fn get_or_create_device(
+        &self,
+        name: &str,
+        device_type_id: &str,
+        runtime_id: &str,
+    ) -> Result<Device> {
+        let list = self.list()?;
+        let existing = list.devices().iter().find(|device| {
+            device.is_available
+                && device.name == name
+                && device.device_type_identifier == device_type_id
+                && device.runtime_identifier == runtime_id
+        });
+
+        match existing {
+            Some(device) => Ok(device.clone()),
+            None => self.create(name, device_type_id, runtime_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    fn test_get_or_create_device() -> Result<()> {
+        let device = mock::device()?;
+        let simctl = device.simctl();
+
+        let created = simctl.get_or_create_device(
+            "simctl get_or_create",
+            &device.device_type_identifier,
+            &device.runtime_identifier,
+        )?;
+        let existing = simctl.get_or_create_device(
+            "simctl get_or_create",
+            &device.device_type_identifier,
+            &device.runtime_identifier,
+        )?;
+        assert_eq!(created.udid, existing.udid);
+
+        simctl.raw("delete", &[&created.udid])?;
+
+        Ok(())
+    }
+}
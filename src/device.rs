@@ -1,7 +1,9 @@
 use std::ops::Deref;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use super::list::DeviceInfo;
-use super::Simctl;
+use super::list::{DeviceInfo, DeviceState};
+use super::{Error, Result, Simctl};
 
 /// Wrapper around a single device returned by `simctl`.
 #[derive(Clone, Debug)]
@@ -26,6 +28,71 @@ impl Device {
     pub fn info(&self) -> &DeviceInfo {
         &self.info
     }
+
+    /// Returns the current state of this device. In contrast to
+    /// [`DeviceInfo::state`], which is a snapshot from when this device was
+    /// retrieved, this queries `simctl` again. Returns
+    /// [`Error::DeviceNotFound`] if the device no longer exists.
+    pub fn current_state(&self) -> Result<DeviceState> {
+        Ok(self.fetch()?.info.state)
+    }
+
+    /// Returns a boolean that indicates if this device is currently booted (see
+    /// [`Device::current_state`]). In contrast to [`DeviceInfo::is_booted`],
+    /// this queries `simctl` again.
+    pub fn is_currently_booted(&self) -> Result<bool> {
+        Ok(self.current_state()? == DeviceState::Booted)
+    }
+
+    /// Returns a boolean that indicates if this device is currently shutdown
+    /// (see [`Device::current_state`]). In contrast to
+    /// [`DeviceInfo::is_shutdown`], this queries `simctl` again.
+    pub fn is_currently_shutdown(&self) -> Result<bool> {
+        Ok(self.current_state()? == DeviceState::Shutdown)
+    }
+
+    /// Polls the current state of this device (see [`Device::current_state`])
+    /// until it matches the given state. Returns [`Error::Timeout`] if the
+    /// device didn't reach the given state before the timeout elapsed. In
+    /// contrast to [`Device::wait_for_boot`], this can also be used to wait for
+    /// a device to shut down.
+    pub fn wait_for_state(&self, state: DeviceState, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if self.current_state()? == state {
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout);
+            }
+
+            thread::sleep(Duration::from_millis(250));
+        }
+    }
+
+    /// Refreshes the information about this device (see [`Device::info`]) by
+    /// querying `simctl` again. Returns [`Error::DeviceNotFound`] if the device
+    /// no longer exists.
+    pub fn refresh(&mut self) -> Result<()> {
+        self.info = self.fetch()?.info;
+        Ok(())
+    }
+
+    /// Opens the visual simulator interface (i.e. Simulator.app) and shows the
+    /// window of this device. This does not boot the device: use
+    /// [`Device::boot_and_open`] to do both.
+    pub fn show(&self) -> Result<()> {
+        self.simctl.open_device(&self.info.udid)
+    }
+
+    fn fetch(&self) -> Result<Device> {
+        self.simctl
+            .list()?
+            .into_device(&self.info.udid)
+            .ok_or_else(|| Error::device_not_found(&self.info.udid))
+    }
 }
 
 impl Deref for Device {
@@ -47,6 +114,30 @@ pub trait DeviceQuery<'a>: Iterator<Item = &'a Device> {
     /// among several devices of the same type but with different runtimes (e.g.
     /// iOS 11.0 and iOS 12.0).
     fn by_name<'b>(self, name: &'b str) -> ByName<'a, 'b, Self>;
+
+    /// Filters this iterator down to only devices with a matching runtime
+    /// identifier (see [`DeviceInfo::runtime_identifier`]).
+    fn by_runtime<'b>(self, runtime_id: &'b str) -> ByRuntime<'a, 'b, Self>
+    where
+        Self: Sized;
+
+    /// Filters this iterator down to only devices with a matching device type
+    /// identifier (see [`DeviceInfo::device_type_identifier`]).
+    fn by_device_type<'b>(self, type_id: &'b str) -> ByDeviceType<'a, 'b, Self>
+    where
+        Self: Sized;
+
+    /// Filters this iterator down to only booted devices.
+    fn booted(self) -> Booted<'a, Self>
+    where
+        Self: Sized;
+
+    /// Filters this iterator down to only the device with the given UDID. In
+    /// contrast to names, UDIDs are unique so this will yield at most one
+    /// device.
+    fn by_udid<'b>(self, udid: &'b str) -> ByUdid<'a, 'b, Self>
+    where
+        Self: Sized;
 }
 
 pub struct Available<'a, I>(I)
@@ -91,6 +182,79 @@ where
     }
 }
 
+/// Iterator adapter that is returned by [`DeviceQuery::by_runtime`].
+pub struct ByRuntime<'a, 'b, I>(&'b str, I)
+where
+    I: Iterator<Item = &'a Device>;
+
+impl<'a, I> Iterator for ByRuntime<'a, '_, I>
+where
+    I: Iterator<Item = &'a Device>,
+{
+    type Item = &'a Device;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let runtime_id = self.0;
+        self.1
+            .by_ref()
+            .find(|device| device.runtime_identifier == runtime_id)
+    }
+}
+
+/// Iterator adapter that is returned by [`DeviceQuery::by_device_type`].
+pub struct ByDeviceType<'a, 'b, I>(&'b str, I)
+where
+    I: Iterator<Item = &'a Device>;
+
+impl<'a, I> Iterator for ByDeviceType<'a, '_, I>
+where
+    I: Iterator<Item = &'a Device>,
+{
+    type Item = &'a Device;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let type_id = self.0;
+        self.1
+            .by_ref()
+            .find(|device| device.device_type_identifier == type_id)
+    }
+}
+
+/// Iterator adapter that is returned by [`DeviceQuery::booted`].
+pub struct Booted<'a, I>(I)
+where
+    I: Iterator<Item = &'a Device>;
+
+impl<'a, I> Iterator for Booted<'a, I>
+where
+    I: Iterator<Item = &'a Device>,
+{
+    type Item = &'a Device;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .by_ref()
+            .find(|device| device.state == DeviceState::Booted)
+    }
+}
+
+/// Iterator adapter that is returned by [`DeviceQuery::by_udid`].
+pub struct ByUdid<'a, 'b, I>(&'b str, I)
+where
+    I: Iterator<Item = &'a Device>;
+
+impl<'a, I> Iterator for ByUdid<'a, '_, I>
+where
+    I: Iterator<Item = &'a Device>,
+{
+    type Item = &'a Device;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let udid = self.0;
+        self.1.by_ref().find(|device| device.udid == udid)
+    }
+}
+
 impl<'a, I> DeviceQuery<'a> for I
 where
     I: Iterator<Item = &'a Device>,
@@ -102,4 +266,272 @@ where
     fn by_name<'b>(self, name: &'b str) -> ByName<'a, 'b, Self> {
         ByName(name, self)
     }
+
+    fn by_runtime<'b>(self, runtime_id: &'b str) -> ByRuntime<'a, 'b, Self> {
+        ByRuntime(runtime_id, self)
+    }
+
+    fn by_device_type<'b>(self, type_id: &'b str) -> ByDeviceType<'a, 'b, Self> {
+        ByDeviceType(type_id, self)
+    }
+
+    fn booted(self) -> Booted<'a, Self> {
+        Booted(self)
+    }
+
+    fn by_udid<'b>(self, udid: &'b str) -> ByUdid<'a, 'b, Self> {
+        ByUdid(udid, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    fn test_by_runtime() -> Result<()> {
+        let device = mock::device()?;
+        let list = device.simctl().list()?;
+        let devices = list
+            .devices()
+            .iter()
+            .by_runtime(&device.runtime_identifier)
+            .collect::<Vec<_>>();
+
+        assert!(!devices.is_empty());
+        assert!(devices
+            .iter()
+            .all(|next| next.runtime_identifier == device.runtime_identifier));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_by_device_type() -> Result<()> {
+        let device = mock::device()?;
+        let list = device.simctl().list()?;
+        let devices = list
+            .devices()
+            .iter()
+            .by_device_type(&device.device_type_identifier)
+            .collect::<Vec<_>>();
+
+        assert!(!devices.is_empty());
+        assert!(devices
+            .iter()
+            .all(|next| next.device_type_identifier == device.device_type_identifier));
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_booted() -> Result<()> {
+        mock::device()?.boot()?;
+
+        let list = mock::device()?.simctl().list()?;
+        assert!(list.devices().iter().booted().count() >= 1);
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_by_udid() -> Result<()> {
+        let device = mock::device()?;
+        let list = device.simctl().list()?;
+        let devices = list
+            .devices()
+            .iter()
+            .by_udid(&device.udid)
+            .collect::<Vec<_>>();
+
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].udid, device.udid);
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_current_state() -> Result<()> {
+        let device = mock::device()?;
+        assert_eq!(device.current_state()?, DeviceState::Shutdown);
+
+        device.boot()?;
+        assert_eq!(device.state, DeviceState::Shutdown);
+        assert_eq!(device.current_state()?, DeviceState::Booted);
+
+        device.shutdown()?;
+        assert_eq!(device.current_state()?, DeviceState::Shutdown);
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_is_booted() -> Result<()> {
+        let device = mock::device()?;
+        assert!(device.is_shutdown() && !device.is_booted());
+        assert!(device.is_currently_shutdown()?);
+
+        device.boot()?;
+        assert!(device.is_currently_booted()?);
+        assert!(!device.is_currently_shutdown()?);
+
+        let device = mock::device()?;
+        assert_eq!(device.is_booted(), device.state == DeviceState::Booted);
+        assert!(device.is_booted());
+
+        device.shutdown()?;
+        assert!(mock::device()?.is_shutdown());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixture_is_booted() -> Result<()> {
+        assert!(mock::fixture_device("iPhone 12")?.is_booted());
+        assert!(mock::fixture_device("iPhone SE (2nd generation)")?.is_shutdown());
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_wait_for_state() -> Result<()> {
+        let device = mock::device()?;
+
+        device.boot()?;
+        device.wait_for_state(DeviceState::Booted, Duration::from_secs(60))?;
+
+        device.shutdown()?;
+        device.wait_for_state(DeviceState::Shutdown, Duration::from_secs(60))?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    #[ignore = "opens the Simulator.app GUI"]
+    fn test_show() -> Result<()> {
+        let device = mock::device()?;
+
+        device.boot()?;
+        device.show()?;
+        device.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_refresh() -> Result<()> {
+        let mut device = mock::device()?;
+        device.clone().boot()?;
+        assert_eq!(device.state, DeviceState::Shutdown);
+
+        device.refresh()?;
+        assert_eq!(device.state, DeviceState::Booted);
+
+        device.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixture_available() -> Result<()> {
+        let list = mock::fixture()?;
+
+        assert_eq!(list.devices().len(), 5);
+        assert_eq!(list.devices().iter().available().count(), 4);
+        assert_eq!(
+            list.devices()
+                .iter()
+                .by_name("iPhone SE (2nd generation)")
+                .count(),
+            2
+        );
+        assert_eq!(
+            list.devices()
+                .iter()
+                .available()
+                .by_name("iPhone SE (2nd generation)")
+                .count(),
+            1
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixture_by_runtime() -> Result<()> {
+        let list = mock::fixture()?;
+        let names = list
+            .devices()
+            .iter()
+            .by_runtime("com.apple.CoreSimulator.SimRuntime.iOS-14-2")
+            .map(|device| device.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["iPhone SE (2nd generation)", "iPhone 12"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixture_by_device_type() -> Result<()> {
+        let list = mock::fixture()?;
+
+        assert_eq!(
+            list.devices()
+                .iter()
+                .by_device_type("com.apple.CoreSimulator.SimDeviceType.Apple-TV-4K-4K")
+                .count(),
+            1
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixture_booted() -> Result<()> {
+        let list = mock::fixture()?;
+        let booted = list.devices().iter().booted().collect::<Vec<_>>();
+
+        assert_eq!(booted.len(), 1);
+        assert_eq!(booted[0].name, "iPhone 12");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixture_by_udid() -> Result<()> {
+        let list = mock::fixture()?;
+        let udid = "C4B2E8A1-7F3D-4A9C-B6E2-1D5F8A3C9E04";
+
+        let device = list.devices().iter().by_udid(udid).next().unwrap();
+        assert_eq!(device.name, "Apple Watch Series 6 - 44mm");
+        assert!(list.devices().iter().by_udid("unknown").next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrappers_clone_debug() -> Result<()> {
+        let device = mock::fixture_device("iPhone SE (2nd generation)")?;
+
+        assert!(format!("{:?}", device.io().clone()).contains(&device.udid));
+        assert!(format!("{:?}", device.keychain().clone()).contains(&device.udid));
+        assert!(format!("{:?}", device.location().clone()).contains(&device.udid));
+        assert!(format!("{:?}", device.privacy().clone()).contains(&device.udid));
+        assert!(format!("{:?}", device.status_bar().clone()).contains(&device.udid));
+        assert!(format!("{:?}", device.ui().clone()).contains(&device.udid));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,135 @@
+//! Supporting types for the `simctl diagnose` subcommand.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{Execute, Result, Simctl, Validate};
+
+/// Builder that can be used to customize the collection of diagnostics.
+#[derive(Debug)]
+pub struct Diagnose<'a> {
+    simctl: Simctl,
+    archive: bool,
+    output_dir: Option<&'a Path>,
+}
+
+impl<'a> Diagnose<'a> {
+    /// Indicates whether the collected diagnostics should be compressed into an
+    /// archive. If disabled, only the output directory is left behind. This is
+    /// enabled by default.
+    pub fn archive(&mut self, archive: bool) -> &mut Diagnose<'a> {
+        self.archive = archive;
+        self
+    }
+
+    /// Writes the collected diagnostics to the given directory. By default, a
+    /// new directory is created in the system's temporary directory.
+    pub fn output_dir<P>(&mut self, path: &'a P) -> &mut Diagnose<'a>
+    where
+        P: AsRef<Path>,
+    {
+        self.output_dir = Some(path.as_ref());
+        self
+    }
+
+    /// Collects the diagnostics and returns a path to the resulting archive
+    /// (or to the output directory if archiving is disabled).
+    pub fn exec(&mut self) -> Result<PathBuf> {
+        let output_dir = match self.output_dir {
+            Some(output_dir) => output_dir.to_path_buf(),
+            None => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_millis())
+                    .unwrap_or_default();
+
+                std::env::temp_dir().join(format!("simctl_diagnose_{}", timestamp))
+            }
+        };
+
+        let mut command = self.simctl.command("diagnose");
+
+        command.arg("-b");
+
+        if !self.archive {
+            command.arg("--no-archive");
+        }
+
+        command.arg(format!("--output-dir={}", output_dir.display()));
+
+        command.execute(&self.simctl)?.validate()?;
+
+        match self.archive {
+            true => {
+                let mut archive = output_dir.into_os_string();
+                archive.push(".tar.gz");
+                Ok(PathBuf::from(archive))
+            }
+            false => Ok(output_dir),
+        }
+    }
+}
+
+impl Simctl {
+    /// Returns a builder that can be used to collect diagnostics (e.g. logs and
+    /// crash reports) of all devices without prompting for confirmation.
+    pub fn diagnose<'a>(&self) -> Diagnose<'a> {
+        Diagnose {
+            simctl: self.clone(),
+            archive: true,
+            output_dir: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    #[ignore = "collecting diagnostics of all devices takes several minutes"]
+    fn test_diagnose() -> Result<()> {
+        let path = Simctl::new().diagnose().archive(false).exec()?;
+        assert!(path.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagnose_stub() -> Result<()> {
+        // The stub only accepts the exact arguments that are expected and
+        // creates the output just like the CLI does.
+        let simctl = mock::stub(
+            "diagnose",
+            "[ \"$1 $2\" = \"diagnose -b\" ] || exit 1\n\
+             case \"$3\" in\n\
+             --no-archive) [ \"$#\" = 4 ] && mkdir -p \"${4#--output-dir=}\" ;;\n\
+             --output-dir=*) [ \"$#\" = 3 ] && touch \"${3#--output-dir=}.tar.gz\" ;;\n\
+             *) exit 1 ;;\n\
+             esac",
+        )?;
+        let output_dir = std::env::temp_dir().join("simctl_diagnose_stub");
+        let archive = std::env::temp_dir().join("simctl_diagnose_stub.tar.gz");
+        let _ = std::fs::remove_file(&archive);
+
+        assert_eq!(simctl.diagnose().output_dir(&output_dir).exec()?, archive);
+        assert!(archive.is_file());
+
+        let path = simctl
+            .diagnose()
+            .output_dir(&output_dir)
+            .archive(false)
+            .exec()?;
+        assert_eq!(path, output_dir);
+        assert!(path.is_dir());
+
+        std::fs::remove_file(&archive)?;
+        std::fs::remove_dir(&output_dir)?;
+
+        Ok(())
+    }
+}
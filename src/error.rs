@@ -1,3 +1,4 @@
+use std::path::Path;
 use std::process::{ExitStatus, Output};
 
 /// Error that is returned when the CLI does not successfully complete a
@@ -19,6 +20,31 @@ pub enum Error {
         status: ExitStatus,
     },
 
+    /// This error is returned when the library rejects an argument before
+    /// invoking the CLI (e.g. because it is out of range). Contains a
+    /// description of the problem.
+    InvalidArgument(String),
+
+    /// This error is returned when the library knows in advance that an
+    /// operation is not supported by the device (e.g. changing the appearance
+    /// of a watchOS device). Contains a description of the problem.
+    Unsupported(String),
+
+    /// This error is returned when the device with the given UDID does not
+    /// exist (anymore), e.g. because it was deleted after it was retrieved.
+    /// Use [`crate::Simctl::list`] to obtain an up-to-date list of devices.
+    DeviceNotFound {
+        /// Contains the UDID of the device that could not be found.
+        udid: String,
+    },
+
+    /// This error is returned by batch operations (e.g.
+    /// [`crate::Simctl::boot_all`] or [`crate::Device::install_all`]) when the
+    /// operation failed for one or more items. Contains an identifier of each
+    /// of those items (e.g. the UDID of a device or the path of an app) along
+    /// with the error that was returned for it.
+    Multiple(Vec<(String, Error)>),
+
     /// This error is returned when the library failed spawning a new process
     /// that runs the CLI. Most likely, this is caused by an incorrect Xcode
     /// path. If the Xcode path was set automatically, Xcode is probably not
@@ -32,11 +58,103 @@ pub enum Error {
     /// serialize a request for `simctl push` (in [`crate::push`]).
     Json(serde_json::Error),
 
+    /// This error is returned when the library failed to deserialize a
+    /// property list (e.g. the response of `simctl listapps` in
+    /// [`crate::list_apps`]).
+    Plist(plist::Error),
+
+    /// This error is returned when the CLI did not complete within the timeout
+    /// that was set with [`crate::Simctl::with_timeout`], or when a device did
+    /// not reach the expected state within the timeout that was passed to a
+    /// polling operation (e.g. [`crate::Device::wait_for_state`]).
+    Timeout,
+
     /// This error is returned when the library failed to interpret the CLI's
     /// response as a UTF-8 encoded string.
     Utf8(std::string::FromUtf8Error),
 }
 
+impl Error {
+    /// Returns the exit code of the CLI if this is an [`Error::Output`] and
+    /// the CLI was not terminated by a signal.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            Error::Output { status, .. } => status.code(),
+            _ => None,
+        }
+    }
+
+    /// Returns a boolean that indicates if this error was returned because the
+    /// device that the CLI was asked to boot is already booted.
+    pub fn is_already_booted(&self) -> bool {
+        match self {
+            Error::Output { stderr, .. } => stderr.contains("current state: Booted"),
+            _ => false,
+        }
+    }
+
+    /// Returns a boolean that indicates if this error was returned because the
+    /// CLI did not recognize the given device (e.g. because it was deleted).
+    /// This includes [`Error::DeviceNotFound`].
+    pub fn is_device_not_found(&self) -> bool {
+        matches!(self, Error::DeviceNotFound { .. }) || self.is_invalid_device()
+    }
+
+    /// Returns a boolean that indicates if this is an [`Error::Output`] of
+    /// which stderr reports an invalid device (i.e. `Invalid device: <udid>`).
+    /// Operations map these errors to [`Error::DeviceNotFound`], so this is
+    /// mostly relevant for output of [`crate::Simctl::raw`].
+    pub fn is_invalid_device(&self) -> bool {
+        match self {
+            Error::Output { stderr, .. } => stderr.contains("Invalid device"),
+            _ => false,
+        }
+    }
+
+    /// Returns the actionable part of the stderr of an [`Error::Output`] (e.g.
+    /// `Unable to boot device in current state: Booted`), without the
+    /// `An error was encountered processing the command` header and without
+    /// any underlying errors that the CLI appends. Returns `None` for other
+    /// errors or if stderr is empty.
+    pub fn simctl_message(&self) -> Option<&str> {
+        let stderr = match self {
+            Error::Output { stderr, .. } => stderr.as_str(),
+            _ => return None,
+        };
+
+        let message = match stderr.trim_start().starts_with("An error was encountered") {
+            true => stderr
+                .trim_start()
+                .split_once('\n')
+                .map_or("", |(_, rest)| rest),
+            false => stderr,
+        };
+
+        let message = match message.find("Underlying error") {
+            Some(index) => &message[..index],
+            None => message,
+        };
+
+        Some(message.trim()).filter(|message| !message.is_empty())
+    }
+
+    /// Returns an [`Error::DeviceNotFound`] for the given UDID.
+    pub(crate) fn device_not_found(udid: &str) -> Error {
+        Error::DeviceNotFound {
+            udid: udid.to_owned(),
+        }
+    }
+}
+
+/// Returns the UDID that the CLI reports as invalid in the given stderr (i.e.
+/// `Invalid device: <udid>`), if any.
+fn invalid_device(stderr: &str) -> Option<&str> {
+    stderr
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Invalid device:"))
+        .map(str::trim)
+}
+
 impl From<std::io::Error> for Error {
     fn from(error: std::io::Error) -> Self {
         Error::Io(error)
@@ -49,6 +167,12 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+impl From<plist::Error> for Error {
+    fn from(error: plist::Error) -> Self {
+        Error::Plist(error)
+    }
+}
+
 impl From<std::string::FromUtf8Error> for Error {
     fn from(error: std::string::FromUtf8Error) -> Self {
         Error::Utf8(error)
@@ -59,6 +183,18 @@ impl From<std::string::FromUtf8Error> for Error {
 /// pre-applied.
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Returns an [`Error::Io`] if the given path does not exist. This is used to
+/// fail early with a clear error, rather than with the CLI's error.
+pub fn ensure_exists(path: &Path) -> Result<()> {
+    match path.exists() {
+        true => Ok(()),
+        false => Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("{} does not exist", path.display()),
+        ))),
+    }
+}
+
 pub trait Validate {
     fn validate(self) -> Result<()>;
     fn validate_with_output(self) -> Result<Output>;
@@ -71,13 +207,119 @@ impl Validate for Output {
     }
 
     fn validate_with_output(self) -> Result<Output> {
-        match self.status.success() {
-            true => Ok(self),
-            false => Err(Error::Output {
-                stdout: String::from_utf8(self.stdout).unwrap(),
-                stderr: String::from_utf8(self.stderr).unwrap(),
-                status: self.status,
-            }),
+        if self.status.success() {
+            return Ok(self);
+        }
+
+        let stderr = String::from_utf8(self.stderr).unwrap();
+
+        if let Some(udid) = invalid_device(&stderr) {
+            return Err(Error::device_not_found(udid));
+        }
+
+        Err(Error::Output {
+            stdout: String::from_utf8(self.stdout).unwrap(),
+            stderr,
+            status: self.status,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::process::ExitStatusExt;
+
+    use super::*;
+
+    fn output(code: i32, stderr: &str) -> Error {
+        Error::Output {
+            stdout: String::new(),
+            stderr: stderr.to_owned(),
+            status: ExitStatus::from_raw(code << 8),
         }
     }
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(output(164, "").exit_code(), Some(164));
+        assert_eq!(Error::Timeout.exit_code(), None);
+    }
+
+    #[test]
+    fn test_is_already_booted() {
+        let error = output(
+            149,
+            "An error was encountered processing the command (domain=com.apple.CoreSimulator.SimError, code=405):\n\
+             Unable to boot device in current state: Booted\n",
+        );
+
+        assert!(error.is_already_booted());
+        assert!(!error.is_device_not_found());
+    }
+
+    #[test]
+    fn test_is_device_not_found() {
+        let error = output(
+            148,
+            "Invalid device: 00000000-0000-0000-0000-000000000000\n",
+        );
+
+        assert!(error.is_device_not_found());
+        assert!(error.is_invalid_device());
+        assert!(!error.is_already_booted());
+        assert!(!Error::Timeout.is_device_not_found());
+    }
+
+    #[test]
+    fn test_simctl_message() {
+        let error = output(
+            149,
+            "An error was encountered processing the command (domain=com.apple.CoreSimulator.SimError, code=405):\n\
+             Unable to boot device in current state: Booted\n",
+        );
+        assert_eq!(
+            error.simctl_message(),
+            Some("Unable to boot device in current state: Booted")
+        );
+
+        let error = output(
+            1,
+            "An error was encountered processing the command (domain=NSPOSIXErrorDomain, code=2):\n\
+             Failed to install the requested application\n\
+             The application's Info.plist does not contain CFBundleVersion.\n\
+             Underlying error (domain=NSPOSIXErrorDomain, code=2):\n\
+             \tNo such file or directory\n",
+        );
+        assert_eq!(
+            error.simctl_message(),
+            Some(
+                "Failed to install the requested application\n\
+                 The application's Info.plist does not contain CFBundleVersion."
+            )
+        );
+
+        assert_eq!(
+            output(2, "Usage: simctl boot <device>\n").simctl_message(),
+            Some("Usage: simctl boot <device>")
+        );
+        assert_eq!(output(2, "").simctl_message(), None);
+        assert_eq!(Error::Timeout.simctl_message(), None);
+    }
+
+    #[test]
+    fn test_validate_invalid_device() {
+        let output = Output {
+            status: ExitStatus::from_raw(148 << 8),
+            stdout: vec![],
+            stderr: b"Invalid device: 00000000-0000-0000-0000-000000000000\n".to_vec(),
+        };
+
+        let error = output.validate().unwrap_err();
+        assert!(error.is_device_not_found());
+        assert!(!error.is_invalid_device());
+        assert!(matches!(
+            error,
+            Error::DeviceNotFound { udid } if udid == "00000000-0000-0000-0000-000000000000"
+        ));
+    }
 }
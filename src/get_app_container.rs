@@ -1,9 +1,11 @@
 //! Supporting types for the `simctl get_app_container` subcommand.
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::str::FromStr;
 
-use super::{Device, Result, Validate};
+use super::{Device, Error, Execute, Result, Validate};
 
 /// Identifies a container that iOS stores a particular kind of data in.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -21,10 +23,52 @@ pub enum Container {
     Group(String),
 }
 
+impl Container {
+    /// Returns an error if this is a group container with an identifier that
+    /// doesn't look like an app group identifier (e.g. `group.com.example`).
+    fn validate(&self) -> Result<()> {
+        match self {
+            Container::Group(group) => {
+                let name = group.strip_prefix("group.").unwrap_or_default();
+
+                if name.is_empty() || name.chars().any(char::is_whitespace) {
+                    return Err(Error::InvalidArgument(format!(
+                        "app group identifiers must start with `group.`: {}",
+                        group
+                    )));
+                }
+
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromStr for Container {
+    type Err = Error;
+
+    /// Parses `app`, `data` or an app group identifier (e.g.
+    /// `group.com.example`) into a container.
+    fn from_str(s: &str) -> Result<Self> {
+        let container = match s {
+            "app" => Container::App,
+            "data" => Container::Data,
+            group => Container::Group(group.to_owned()),
+        };
+
+        container.validate()?;
+
+        Ok(container)
+    }
+}
+
 impl Device {
     /// Returns a path to the given container of an application with the given
     /// bundle id.
     pub fn get_app_container(&self, bundle_id: &str, container: &Container) -> Result<PathBuf> {
+        container.validate()?;
+
         let container = match container {
             Container::App => "app",
             Container::Data => "data",
@@ -38,18 +82,62 @@ impl Device {
             .arg(bundle_id)
             .arg(container)
             .stdout(Stdio::piped())
-            .output()?;
+            .execute(self.simctl())?;
 
         let output = output.validate_with_output()?;
 
         Ok(Path::new(String::from_utf8(output.stdout)?.trim()).to_path_buf())
     }
+
+    /// Returns the paths to all containers of an application with the given
+    /// bundle id, keyed by `app`, `data` and the identifier of each app group
+    /// the application belongs to.
+    ///
+    /// This is a convenience wrapper that invokes the CLI three times (once
+    /// for each of `app`, `data` and `groups`). Use
+    /// [`Device::get_app_container`] if only one of the containers is needed.
+    pub fn get_app_containers(&self, bundle_id: &str) -> Result<HashMap<String, PathBuf>> {
+        let mut containers = HashMap::new();
+        containers.insert(
+            "app".to_owned(),
+            self.get_app_container(bundle_id, &Container::App)?,
+        );
+        containers.insert(
+            "data".to_owned(),
+            self.get_app_container(bundle_id, &Container::Data)?,
+        );
+
+        let output = self
+            .simctl()
+            .command("get_app_container")
+            .arg(&self.udid)
+            .arg(bundle_id)
+            .arg("groups")
+            .stdout(Stdio::piped())
+            .execute(self.simctl())?;
+
+        let output = output.validate_with_output()?;
+
+        // Each line contains a group identifier followed by the path to its
+        // container, separated by whitespace.
+        for line in String::from_utf8(output.stdout)?.lines() {
+            let line = line.trim();
+            if let Some(index) = line.find(char::is_whitespace) {
+                let (group, path) = line.split_at(index);
+                containers.insert(group.to_owned(), PathBuf::from(path.trim()));
+            }
+        }
+
+        Ok(containers)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use serial_test::serial;
 
+    use std::path::Path;
+
     use super::*;
     use crate::mock;
 
@@ -68,4 +156,89 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_get_app_containers() -> Result<()> {
+        let mut path = Path::new(env!("CARGO_MANIFEST_DIR")).to_path_buf();
+        path.push("tests/Example.app");
+
+        mock::device()?.boot()?;
+        mock::device()?.install(&path)?;
+
+        let containers = mock::device()?.get_app_containers("com.glacyr.simctl.Example")?;
+        assert!(containers.contains_key("app"));
+        assert!(containers.contains_key("data"));
+
+        mock::device()?.uninstall("com.glacyr.simctl.Example")?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_container_from_str() -> Result<()> {
+        assert_eq!(Container::from_str("app")?, Container::App);
+        assert_eq!(Container::from_str("data")?, Container::Data);
+        assert_eq!(
+            Container::from_str("group.com.example")?,
+            Container::Group("group.com.example".to_owned())
+        );
+        assert!(Container::from_str("com.example").is_err());
+        assert!(Container::from_str("group.").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_app_container_invalid_group() -> Result<()> {
+        let device = mock::fixture_device("iPhone SE (2nd generation)")?;
+
+        assert!(matches!(
+            device.get_app_container(
+                "com.apple.mobilesafari",
+                &Container::Group("com.example".to_owned())
+            ),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        Ok(())
+    }
+    #[test]
+    fn test_get_app_containers_stub() -> Result<()> {
+        let simctl = mock::stub(
+            "get_app_containers",
+            r#"case "$4" in
+app) echo "/Containers/Bundle/Example.app" ;;
+data) echo "/Containers/Data/Example" ;;
+groups) printf '%s\t%s\n' 'group.com.glacyr.simctl' '/Containers/Shared/Group One' 'group.com.glacyr.other' '/Containers/Shared/Two' ;;
+*) exit 1 ;;
+esac"#,
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        let mut containers = device
+            .get_app_containers("com.glacyr.simctl.Example")?
+            .into_iter()
+            .collect::<Vec<_>>();
+        containers.sort();
+
+        assert_eq!(
+            containers,
+            vec![
+                ("app".to_owned(), "/Containers/Bundle/Example.app".into()),
+                ("data".to_owned(), "/Containers/Data/Example".into()),
+                (
+                    "group.com.glacyr.other".to_owned(),
+                    "/Containers/Shared/Two".into()
+                ),
+                (
+                    "group.com.glacyr.simctl".to_owned(),
+                    "/Containers/Shared/Group One".into()
+                ),
+            ]
+        );
+
+        Ok(())
+    }
 }
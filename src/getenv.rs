@@ -1,23 +1,37 @@
 use std::process::Stdio;
 
-use super::{Device, Result, Validate};
+use super::{Device, Execute, Result, Validate};
 
 impl Device {
     /// Returns a local environment variable with the given name. Do not prepend
     /// `SIMCTL_CHILD_` to the variable name. If no variable with the given name
-    /// exists, this function will return an empty string (and no error).
+    /// exists, this function will return an empty string (and no error). Use
+    /// [`Device::getenv_opt`] to distinguish between these cases.
     pub fn getenv(&self, name: &str) -> Result<String> {
+        Ok(self.getenv_opt(name)?.unwrap_or_default())
+    }
+
+    /// Returns a local environment variable with the given name, or `None` if
+    /// no variable with the given name exists. In contrast to
+    /// [`Device::getenv`], a variable that is set to an empty string is
+    /// returned as `Some("")`.
+    pub fn getenv_opt(&self, name: &str) -> Result<Option<String>> {
         let output = self
             .simctl()
             .command("getenv")
             .arg(&self.udid)
             .arg(&name)
             .stdout(Stdio::piped())
-            .output()?;
+            .execute(self.simctl())?;
 
         let output = output.validate_with_output()?;
 
-        Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+        // The CLI writes nothing if the variable doesn't exist and the value
+        // followed by a newline otherwise (even if the value is empty).
+        match output.stdout.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(String::from_utf8(output.stdout)?.trim().to_owned())),
+        }
     }
 }
 
@@ -38,4 +52,35 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_getenv_opt() -> Result<()> {
+        mock::device()?.boot_with_env(vec![("TEST_VAR", ""), ("OTHER_VAR", "1")])?;
+        assert_eq!(mock::device()?.getenv_opt("TEST_VAR")?.as_deref(), Some(""));
+        assert_eq!(
+            mock::device()?.getenv_opt("OTHER_VAR")?.as_deref(),
+            Some("1")
+        );
+        assert_eq!(mock::device()?.getenv_opt("TEST_VAR_")?, None);
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_getenv_opt_stub() -> Result<()> {
+        let simctl = mock::stub(
+            "getenv_opt",
+            r#"case "$3" in EMPTY) echo ;; SET) echo 'Hello World!' ;; esac"#,
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        assert_eq!(device.getenv_opt("EMPTY")?.as_deref(), Some(""));
+        assert_eq!(device.getenv_opt("SET")?.as_deref(), Some("Hello World!"));
+        assert_eq!(device.getenv_opt("UNSET")?, None);
+        assert_eq!(device.getenv("UNSET")?, "");
+
+        Ok(())
+    }
 }
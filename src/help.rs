@@ -0,0 +1,79 @@
+//! Supporting types for discovering the subcommands of simctl.
+
+use std::process::Stdio;
+
+use super::{Execute, Result, Simctl, Validate};
+
+/// Parses the names of the subcommands that are listed in the output of
+/// `simctl help`.
+fn parse_subcommands(help: &str) -> Vec<String> {
+    help.lines()
+        .skip_while(|line| !line.starts_with("Subcommands:"))
+        .skip(1)
+        .take_while(|line| line.starts_with(char::is_whitespace))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|name| name.to_owned())
+        .collect()
+}
+
+impl Simctl {
+    /// Returns the names of all subcommands that are supported by the `simctl`
+    /// utility of this Xcode installation (e.g. `boot` and `launch`), as
+    /// listed by `simctl help`. The list is obtained once and cached afterwards
+    /// (also for clones of this wrapper).
+    pub fn subcommands(&self) -> Result<Vec<String>> {
+        if let Some(subcommands) = self.subcommands.get() {
+            return Ok(subcommands.clone());
+        }
+
+        let output = self.command("help").stdout(Stdio::piped()).execute(self)?;
+
+        let output = output.validate_with_output()?;
+        let subcommands = parse_subcommands(&String::from_utf8(output.stdout)?);
+
+        let _ = self.subcommands.set(subcommands.clone());
+
+        Ok(subcommands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    fn test_subcommands() -> Result<()> {
+        let log = std::env::temp_dir().join("simctl_stub_subcommands.log");
+        let _ = std::fs::remove_file(&log);
+
+        let script = format!(
+            "echo \"$@\" >> '{}'\n\
+             printf 'usage: simctl <subcommand> ...\\n\\nSubcommands:\\n\\tboot    Boot a device.\\n\\tlaunch  Launch an application.\\n'",
+            log.display()
+        );
+
+        let simctl = mock::stub("subcommands", &script)?;
+
+        assert_eq!(simctl.subcommands()?, vec!["boot", "launch"]);
+        assert_eq!(simctl.clone().subcommands()?, vec!["boot", "launch"]);
+        assert_eq!(std::fs::read_to_string(&log)?, "help\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        let help = "usage: simctl [--set <path>] [--profiles <path>] <subcommand> ...
+       simctl help [subcommand]
+Command line utility to control the Simulator
+
+Subcommands:
+\taddmedia            Add photos, live photos, videos, or contacts to the library of a device.
+\tboot                Boot a device or device pair.
+\tlist                List available devices, device types, runtimes, or device pairs.
+";
+
+        assert_eq!(parse_subcommands(help), vec!["addmedia", "boot", "list"]);
+    }
+}
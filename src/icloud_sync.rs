@@ -0,0 +1,32 @@
+use super::{Device, Execute, Result, Validate};
+
+impl Device {
+    /// Triggers an iCloud sync on this device. This is useful when testing apps
+    /// that rely on CloudKit or `NSUbiquitousKeyValueStore`. If the device isn't
+    /// booted, this function will return an error (as does the underlying CLI).
+    pub fn icloud_sync(&self) -> Result<()> {
+        self.simctl()
+            .command("icloud_sync")
+            .arg(&self.udid)
+            .execute(self.simctl())?
+            .validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    fn test_icloud_sync() -> Result<()> {
+        mock::device()?.boot()?;
+        mock::device()?.icloud_sync()?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+}
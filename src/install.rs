@@ -1,6 +1,47 @@
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
+use std::process::Output;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::time::Instant;
 
-use super::{Device, Result, Validate};
+use super::privacy::PrivacyService;
+use super::{ensure_exists, Device, Error, Execute, Result, Validate};
+
+#[derive(Deserialize)]
+struct InfoPlist {
+    #[serde(rename = "CFBundleIdentifier")]
+    bundle_id: String,
+}
+
+/// Returns the bundle ID of the .app folder at the given path by reading the
+/// `CFBundleIdentifier` from its `Info.plist`. This does not invoke the CLI,
+/// so the app doesn't need to be installed.
+pub fn bundle_id_of(path: &Path) -> Result<String> {
+    let info: InfoPlist = plist::from_file(path.join("Info.plist"))?;
+    Ok(info.bundle_id)
+}
+
+/// Sends each line read from the given reader to the given sender from a
+/// background thread, tagged with a boolean that indicates if it was read from
+/// stderr.
+fn forward_lines<R>(reader: R, is_stderr: bool, sender: Sender<(bool, String)>)
+where
+    R: Read + Send + 'static,
+{
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).split(b'\n') {
+            let line = match line {
+                Ok(line) => String::from_utf8_lossy(&line).into_owned(),
+                Err(_) => break,
+            };
+
+            if sender.send((is_stderr, line)).is_err() {
+                break;
+            }
+        }
+    });
+}
 
 impl Device {
     /// Installs an .app folder from the given path onto this device. If the
@@ -13,9 +54,122 @@ impl Device {
             .command("install")
             .arg(&self.udid)
             .arg(&path)
-            .output()?
+            .execute(self.simctl())?
             .validate()
     }
+
+    /// Installs each of the .app folders at the given paths onto this device.
+    /// All paths are checked to exist before anything is installed. Stops at
+    /// the first app that could not be installed, in which case the original
+    /// error is returned in an [`Error::Multiple`] along with the path of that
+    /// app.
+    pub fn install_all<P>(&self, paths: &[P]) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        paths
+            .iter()
+            .try_for_each(|path| ensure_exists(path.as_ref()))?;
+
+        paths.iter().try_for_each(|path| {
+            let path = path.as_ref();
+
+            self.install(path)
+                .map_err(|error| Error::Multiple(vec![(path.display().to_string(), error)]))
+        })
+    }
+
+    /// Installs an .app folder from the given path onto this device, just like
+    /// [`Device::install`], while invoking the given callback with each line
+    /// that the CLI writes to stdout or stderr as soon as it arrives.
+    pub fn install_with_progress<F>(&self, path: &Path, mut on_line: F) -> Result<()>
+    where
+        F: FnMut(&str),
+    {
+        let mut child = self
+            .simctl()
+            .spawn(self.simctl().command("install").arg(&self.udid).arg(path))?;
+
+        let (sender, receiver) = mpsc::channel();
+
+        if let Some(stdout) = child.stdout.take() {
+            forward_lines(stdout, false, sender.clone());
+        }
+
+        if let Some(stderr) = child.stderr.take() {
+            forward_lines(stderr, true, sender);
+        }
+
+        let deadline = self
+            .simctl()
+            .timeout()
+            .map(|timeout| Instant::now() + timeout);
+
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+
+        loop {
+            let received = match deadline {
+                Some(deadline) => {
+                    receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            match received {
+                Ok((is_stderr, line)) => {
+                    on_line(&line);
+
+                    let buffer = if is_stderr { &mut stderr } else { &mut stdout };
+                    buffer.push_str(&line);
+                    buffer.push('\n');
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(Error::Timeout);
+                }
+            }
+        }
+
+        Output {
+            status: child.wait()?,
+            stdout: stdout.into_bytes(),
+            stderr: stderr.into_bytes(),
+        }
+        .validate()
+    }
+
+    /// Installs an .app folder from the given path onto this device and
+    /// subsequently grants it access to each of the given services (see
+    /// [`crate::privacy::Privacy::grant_all`]). Returns the bundle ID of the
+    /// app, which is read from its `Info.plist` before anything is installed.
+    /// Returns [`Error::InvalidArgument`] if the bundle ID can't be determined.
+    pub fn install_configured(&self, path: &Path, services: &[PrivacyService]) -> Result<String> {
+        let bundle_id = bundle_id_of(path).map_err(|error| {
+            Error::InvalidArgument(format!(
+                "could not determine the bundle ID of {}: {:?}",
+                path.display(),
+                error
+            ))
+        })?;
+
+        self.install(path)?;
+        self.privacy().grant_all(services, &bundle_id)?;
+
+        Ok(bundle_id)
+    }
+
+    /// Installs an .app folder from the given path onto this device and
+    /// subsequently launches it. Returns the PID of the launched app. Unlike
+    /// [`Device::launch`], this does not wait for the app to exit.
+    pub fn install_and_launch(&self, path: &Path) -> Result<u32> {
+        let bundle_id = bundle_id_of(path)?;
+
+        self.install(path)?;
+        self.launch(&bundle_id).exec_pid()
+    }
 }
 
 #[cfg(test)]
@@ -38,4 +192,184 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_install_all() -> Result<()> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let paths = [root.join("tests/Example.app"), root.join("tests/Other.app")];
+
+        mock::device()?.boot()?;
+        mock::device()?.install_all(&paths)?;
+        mock::device()?.uninstall("com.glacyr.simctl.Example")?;
+        mock::device()?.uninstall("com.glacyr.simctl.Other")?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_install_all_stub() -> Result<()> {
+        let simctl = mock::stub(
+            "install_all",
+            "case \"$3\" in\n\
+             */Other.app) echo \"An error was encountered processing the command\" >&2\n\
+             echo \"Invalid device: $2\" >&2; exit 1 ;;\n\
+             esac",
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let paths = [root.join("tests/Example.app"), root.join("tests/Other.app")];
+
+        match device.install_all(&paths) {
+            Err(Error::Multiple(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].0, paths[1].display().to_string());
+                assert!(errors[0].1.is_device_not_found());
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_install_all_missing_path() -> Result<()> {
+        let device = mock::fixture_device("iPhone SE (2nd generation)")?;
+
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let paths = [
+            root.join("tests/Example.app"),
+            root.join("tests/Missing.app"),
+        ];
+
+        match device.install_all(&paths) {
+            Err(Error::Io(error)) => assert!(error.to_string().contains("Missing.app")),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_install_configured() -> Result<()> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/Example.app");
+
+        mock::device()?.boot()?;
+
+        let bundle_id = mock::device()?.install_configured(&path, &[PrivacyService::Location])?;
+        assert_eq!(bundle_id, "com.glacyr.simctl.Example");
+
+        mock::device()?.uninstall(&bundle_id)?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bundle_id_of() -> Result<()> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        assert_eq!(
+            bundle_id_of(&root.join("tests/Example.app"))?,
+            "com.glacyr.simctl.Example"
+        );
+        assert!(bundle_id_of(&root.join("tests/Missing.app")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_install_configured_missing_info_plist() -> Result<()> {
+        let device = mock::fixture_device("iPhone SE (2nd generation)")?;
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/Missing.app");
+
+        assert!(matches!(
+            device.install_configured(&path, &[PrivacyService::Location]),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_install_with_progress() -> Result<()> {
+        let mut path = Path::new(env!("CARGO_MANIFEST_DIR")).to_path_buf();
+        path.push("tests/Example.app");
+
+        mock::device()?.boot()?;
+
+        // The CLI is usually quiet when the installation succeeds, so we can't
+        // assert that any lines were reported (see the stub test below).
+        mock::device()?.install_with_progress(&path, |_| {})?;
+        assert!(mock::device()?
+            .list_apps()?
+            .iter()
+            .any(|app| app.bundle_id == "com.glacyr.simctl.Example"));
+
+        mock::device()?.uninstall("com.glacyr.simctl.Example")?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_install_with_progress_stub() -> Result<()> {
+        let simctl = mock::stub(
+            "install_with_progress",
+            "echo \"Installing $3\"\n\
+             sleep 0.1\n\
+             echo \"Copying files\"\n\
+             echo \"Done\"",
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+        let path = Path::new("Example.app");
+
+        let mut lines = vec![];
+        device.install_with_progress(path, |line| lines.push(line.to_owned()))?;
+        assert_eq!(
+            lines,
+            vec!["Installing Example.app", "Copying files", "Done"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_install_and_launch_stub() -> Result<()> {
+        // The stub fails unless the app is launched without a console, which
+        // would otherwise block until the app exits.
+        let simctl = mock::stub(
+            "install_and_launch",
+            "case \"$1\" in\n\
+             install) [ \"$#\" = 3 ] ;;\n\
+             launch) [ \"$#\" = 3 ] && echo \"$3: 4242\" ;;\n\
+             *) exit 1 ;;\n\
+             esac",
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/Example.app");
+
+        assert_eq!(device.install_and_launch(&path)?, 4242);
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    #[ignore = "tests/Example.app does not contain a runnable executable"]
+    fn test_install_and_launch() -> Result<()> {
+        let mut path = Path::new(env!("CARGO_MANIFEST_DIR")).to_path_buf();
+        path.push("tests/Example.app");
+
+        mock::device()?.boot()?;
+        assert!(mock::device()?.install_and_launch(&path)? > 0);
+        mock::device()?.uninstall("com.glacyr.simctl.Example")?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
 }
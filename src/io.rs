@@ -1,8 +1,39 @@
 //! Supporting types for the `simctl io` subcommand.
 
-use std::process::Stdio;
+use std::convert::TryFrom;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use super::{Device, Result, Validate};
+use super::{Device, Error, Execute, Result, Validate};
+
+/// Encodes the given bytes using the standard base64 alphabet (with padding).
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+
+    encoded
+}
 
 /// Distinguishes the display for devices that have multiple.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -12,15 +43,16 @@ pub enum Display {
     Internal,
 
     /// Indicates the external display. The external display is a connected
-    /// display. This is supported by the iOS simulator (although it's currently
-    /// not possible to setup such an external display through this library) and
-    /// tvOS, where it's the only available display because the hardware itself
-    /// obviously doesn't have a display.
+    /// display. This is supported by tvOS, where it's the only available
+    /// display because the hardware itself obviously doesn't have a display.
+    /// The iOS simulator supports external displays too, but only once one
+    /// has been attached (e.g. through the I/O menu of Simulator.app).
     External,
 }
 
 /// Controls the masking behavior that is used when taking a screenshot on
-/// simulators of devices that feature rounded corners or a notch.
+/// simulators of devices that feature rounded corners or a notch. On other
+/// devices, the mask has no effect (see [`IO::supports_mask`]).
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Mask {
     /// Returns the unmasked frame buffer (without any masking applied).
@@ -35,6 +67,70 @@ pub enum Mask {
     Black,
 }
 
+impl Display {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            Display::Internal => "internal",
+            Display::External => "external",
+        }
+    }
+}
+
+/// Parses the displays from the output of `simctl io enumerate`, which lists
+/// each I/O port of a device as a `Port:` header followed by indented
+/// properties. Display ports report a display class, which is 0 for the
+/// internal display and 1 for an external display.
+fn parse_displays(output: &str) -> Vec<Display> {
+    output
+        .split("Port:")
+        .filter(|port| port.lines().any(|line| line.trim() == "Class: Display"))
+        .filter_map(|port| {
+            let class = port
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("Display class:"))?;
+
+            match class.trim() {
+                "0" => Some(Display::Internal),
+                "1" => Some(Display::External),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+impl Mask {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            Mask::Ignored => "ignored",
+            Mask::Alpha => "alpha",
+            Mask::Black => "black",
+        }
+    }
+}
+
+/// Hardware button that can be pressed with [`IO::send_key`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum HardwareKey {
+    /// Indicates the home button.
+    Home,
+
+    /// Indicates the lock (i.e. side or sleep/wake) button.
+    Lock,
+
+    /// Indicates the button that activates Siri.
+    Siri,
+}
+
+impl HardwareKey {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            HardwareKey::Home => "home",
+            HardwareKey::Lock => "lock",
+            HardwareKey::Siri => "siri",
+        }
+    }
+}
+
 /// Controls the encoding that will be used to write a screenshot to the buffer
 /// that is returned.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -55,7 +151,199 @@ pub enum ImageType {
     Jpeg,
 }
 
+impl ImageType {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            ImageType::Png => "png",
+            ImageType::Tiff => "tiff",
+            ImageType::Bmp => "bmp",
+            ImageType::Gif => "gif",
+            ImageType::Jpeg => "jpeg",
+        }
+    }
+
+    /// Returns the image type that corresponds to the given file extension
+    /// (e.g. `jpg`), ignoring case. Returns an error for unknown extensions.
+    pub fn from_extension(extension: &str) -> Result<ImageType> {
+        Ok(match extension.to_ascii_lowercase().as_str() {
+            "png" => ImageType::Png,
+            "tiff" | "tif" => ImageType::Tiff,
+            "bmp" => ImageType::Bmp,
+            "gif" => ImageType::Gif,
+            "jpeg" | "jpg" => ImageType::Jpeg,
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "unknown image type: {}",
+                    extension
+                )))
+            }
+        })
+    }
+
+    fn from_path(path: &Path) -> Result<ImageType> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) => ImageType::from_extension(extension),
+            None => Err(Error::InvalidArgument(format!(
+                "cannot infer image type from path: {}",
+                path.display()
+            ))),
+        }
+    }
+}
+
+impl FromStr for ImageType {
+    type Err = Error;
+
+    /// Parses an image type from a format string or file extension (e.g.
+    /// `png` or `jpg`). See [`ImageType::from_extension`].
+    fn from_str(s: &str) -> Result<Self> {
+        ImageType::from_extension(s)
+    }
+}
+
+impl TryFrom<&str> for ImageType {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        ImageType::from_extension(value)
+    }
+}
+
+/// Metadata of a PNG-encoded screenshot that is read from its header, without
+/// decoding the image itself.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ScreenshotInfo {
+    /// Width of the screenshot in pixels.
+    pub width: u32,
+
+    /// Height of the screenshot in pixels.
+    pub height: u32,
+
+    /// Scale factor of the screenshot (i.e. the number of pixels per point),
+    /// derived from the physical pixel density that is stored in the image.
+    /// This is `None` if the image does not specify its pixel density.
+    pub scale: Option<f64>,
+}
+
+impl ScreenshotInfo {
+    /// Reads the dimensions and pixel density from the given PNG-encoded
+    /// image.
+    fn from_png(bytes: &[u8]) -> Result<ScreenshotInfo> {
+        fn invalid_data() -> Error {
+            Error::Io(std::io::Error::new(
+                ErrorKind::InvalidData,
+                "screenshot is not a valid PNG-encoded image",
+            ))
+        }
+
+        fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+            let bytes = bytes.get(offset..offset + 4)?;
+            Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        }
+
+        if !bytes.starts_with(b"\x89PNG\r\n\x1a\n") || bytes.get(12..16) != Some(b"IHDR") {
+            return Err(invalid_data());
+        }
+
+        let width = read_u32(bytes, 16).ok_or_else(invalid_data)?;
+        let height = read_u32(bytes, 20).ok_or_else(invalid_data)?;
+        let mut scale = None;
+
+        // The pHYs chunk (if present) must precede the first IDAT chunk. Its
+        // density is expressed in pixels per meter, which we compare with the
+        // 72 DPI that corresponds to a scale of 1.
+        let mut offset = 8;
+        while let (Some(length), Some(kind)) =
+            (read_u32(bytes, offset), bytes.get(offset + 4..offset + 8))
+        {
+            match kind {
+                b"pHYs" => {
+                    let pixels_per_meter = read_u32(bytes, offset + 8).ok_or_else(invalid_data)?;
+
+                    if bytes.get(offset + 16) == Some(&1) && pixels_per_meter > 0 {
+                        scale = Some((pixels_per_meter as f64 * 0.0254 / 72.0).round());
+                    }
+
+                    break;
+                }
+                b"IDAT" | b"IEND" => break,
+                _ => offset += 12 + length as usize,
+            }
+        }
+
+        Ok(ScreenshotInfo {
+            width,
+            height,
+            scale,
+        })
+    }
+}
+
+/// Handle to a video recording that is in progress. Use
+/// [`VideoRecording::stop_and_wait`] to finish the recording. If this handle
+/// is dropped before that, the recording is killed and its file is probably
+/// unusable.
+#[derive(Debug)]
+pub struct VideoRecording {
+    path: PathBuf,
+    child: Option<Child>,
+}
+
+impl VideoRecording {
+    /// Returns the path that this recording is written to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stops this recording by interrupting the CLI (which is how it expects to
+    /// be stopped) and waits until it has exited. The CLI finishes writing the
+    /// file before it exits, so the file can safely be read afterwards. If this
+    /// takes longer than the given timeout, the CLI is killed and
+    /// [`Error::Timeout`] is returned.
+    pub fn stop_and_wait(mut self, timeout: Duration) -> Result<()> {
+        let mut child = match self.child.take() {
+            Some(child) => child,
+            None => return Ok(()),
+        };
+
+        // The child is owned (and not yet reaped) by us, so its pid can't have
+        // been reused by another process.
+        if unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) } != 0 {
+            let error = std::io::Error::last_os_error();
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(error.into());
+        }
+
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if child.try_wait()?.is_some() {
+                return child.wait_with_output()?.validate();
+            }
+
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::Timeout);
+            }
+
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+impl Drop for VideoRecording {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
 /// Wrapper around the `simctl io` subcommand.
+#[derive(Clone, Debug)]
 pub struct IO {
     device: Device,
 }
@@ -67,9 +355,120 @@ impl Device {
             device: self.clone(),
         }
     }
+
+    /// Takes a PNG screenshot of the internal display of this device, ignoring
+    /// the mask. This is a shorthand for the most common invocation of
+    /// [`IO::screenshot`]. Note that tvOS devices don't have an internal
+    /// display, so use [`IO::screenshot`] with [`Display::External`] instead.
+    pub fn screenshot_png(&self) -> Result<Vec<u8>> {
+        self.io()
+            .screenshot(ImageType::Png, Display::Internal, Mask::Ignored)
+    }
 }
 
 impl IO {
+    /// Returns the displays of this device, as reported by `simctl io
+    /// enumerate`. The device must be booted. tvOS devices only have an
+    /// external display, while iOS devices have an internal display and
+    /// optionally an external display if one has been attached.
+    pub fn displays(&self) -> Result<Vec<Display>> {
+        let output = self
+            .device
+            .simctl()
+            .command("io")
+            .arg(&self.device.udid)
+            .arg("enumerate")
+            .stdout(Stdio::piped())
+            .execute(self.device.simctl())?;
+
+        let output = output.validate_with_output()?;
+        let mut displays = parse_displays(&String::from_utf8(output.stdout)?);
+
+        // The only display of a TV is always addressed as the external display.
+        if self.device.runtime_identifier.contains("tvOS") {
+            displays = vec![Display::External];
+        }
+
+        Ok(displays)
+    }
+
+    /// Returns a boolean that indicates if this device has the given display
+    /// (see [`IO::displays`]).
+    pub fn supports_display(&self, display: Display) -> Result<bool> {
+        Ok(self.displays()?.contains(&display))
+    }
+
+    /// Returns a boolean that indicates if the given mask can be applied to
+    /// screenshots of this device. Masks other than [`Mask::Ignored`] only
+    /// apply to devices with rounded corners or a notch, which is read from
+    /// the profile of the device's type.
+    pub fn supports_mask(&self, mask: Mask) -> Result<bool> {
+        if mask == Mask::Ignored {
+            return Ok(true);
+        }
+
+        let list = self.device.simctl().list()?;
+        let device_type = list.device_type_of(&self.device).ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "device type is not registered: {}",
+                self.device.device_type_identifier
+            ))
+        })?;
+
+        let profile: plist::Dictionary = plist::from_file(
+            device_type
+                .bundle_path
+                .join("Contents/Resources/profile.plist"),
+        )?;
+
+        Ok(profile.contains_key("framebufferMask"))
+    }
+
+    /// Returns [`Error::Unsupported`] if this device doesn't have the given
+    /// display or if the given mask has no effect on this device. Screenshots
+    /// and recordings don't perform this check themselves, because it requires
+    /// additional invocations of the CLI. Call this beforehand to fail with a
+    /// descriptive error rather than with the CLI's error.
+    pub fn validate_display(&self, display: Display, mask: Mask) -> Result<()> {
+        if !self.supports_display(display)? {
+            return Err(Error::Unsupported(format!(
+                "{} ({}) does not have an {} display",
+                self.device.name,
+                self.device.runtime_identifier,
+                display.as_arg()
+            )));
+        }
+
+        if !self.supports_mask(mask)? {
+            return Err(Error::Unsupported(format!(
+                "{} does not have rounded corners or a notch, so its screenshots can't be masked",
+                self.device.name
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Takes a screenshot of the display of this device (i.e. the external
+    /// display for tvOS devices and the internal display otherwise), ignoring
+    /// the mask, and returns it as a base64-encoded data URI (e.g.
+    /// `data:image/png;base64,...`) that can be embedded in HTML reports.
+    pub fn screenshot_data_uri(&self, image_type: ImageType) -> Result<String> {
+        let display = match self.device.runtime_identifier.contains("tvOS") {
+            true => Display::External,
+            false => Display::Internal,
+        };
+
+        let bytes = self.screenshot(image_type, display, Mask::Ignored)?;
+
+        // Each image type's argument is also its MIME subtype.
+        Ok(format!(
+            "data:image/{};base64,{}",
+            image_type.as_arg(),
+            base64(&bytes)
+        ))
+    }
+
     /// Takes a screenshot of the given display, with the given mask and returns
     /// a buffer of the image encoded using the given type.
     pub fn screenshot(
@@ -78,42 +477,166 @@ impl IO {
         display: Display,
         mask: Mask,
     ) -> Result<Vec<u8>> {
-        let image_type = match image_type {
-            ImageType::Png => "png",
-            ImageType::Tiff => "tiff",
-            ImageType::Bmp => "bmp",
-            ImageType::Gif => "gif",
-            ImageType::Jpeg => "jpeg",
-        };
-
-        let display = match display {
-            Display::Internal => "internal",
-            Display::External => "external",
-        };
-
-        let mask = match mask {
-            Mask::Ignored => "ignored",
-            Mask::Alpha => "alpha",
-            Mask::Black => "black",
-        };
-
         let output = self
             .device
             .simctl()
             .command("io")
             .arg(&self.device.udid)
             .arg("screenshot")
-            .arg(format!("--type={}", image_type))
-            .arg(format!("--display={}", display))
-            .arg(format!("--mask={}", mask))
+            .arg(format!("--type={}", image_type.as_arg()))
+            .arg(format!("--display={}", display.as_arg()))
+            .arg(format!("--mask={}", mask.as_arg()))
             .arg("-")
             .stdout(Stdio::piped())
-            .output()?;
+            .execute(self.device.simctl())?;
 
         let output = output.validate_with_output()?;
 
         Ok(output.stdout)
     }
+
+    /// Takes a screenshot just like [`IO::screenshot`], but retries up to the
+    /// given number of attempts, sleeping for the given delay in between. The
+    /// screenshot service is often not yet available immediately after a device
+    /// has booted (even after [`Device::wait_for_boot`] returns). Returns the
+    /// last error if all attempts fail.
+    pub fn screenshot_with_retry(
+        &self,
+        image_type: ImageType,
+        display: Display,
+        mask: Mask,
+        attempts: usize,
+        delay: Duration,
+    ) -> Result<Vec<u8>> {
+        let mut attempt = 1;
+
+        loop {
+            match self.screenshot(image_type, display, mask) {
+                Err(_) if attempt < attempts => {
+                    attempt += 1;
+                    thread::sleep(delay);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Takes a PNG-encoded screenshot of the given display, with the given mask
+    /// and returns its buffer along with its dimensions.
+    pub fn screenshot_with_info(
+        &self,
+        display: Display,
+        mask: Mask,
+    ) -> Result<(Vec<u8>, ScreenshotInfo)> {
+        let bytes = self.screenshot(ImageType::Png, display, mask)?;
+        let info = ScreenshotInfo::from_png(&bytes)?;
+        Ok((bytes, info))
+    }
+
+    /// Starts recording a video of the given display, with the given mask, to
+    /// the given path. Existing files are overwritten. The recording continues
+    /// until [`VideoRecording::stop_and_wait`] is called.
+    pub fn record_video(
+        &self,
+        path: &Path,
+        display: Display,
+        mask: Mask,
+    ) -> Result<VideoRecording> {
+        let child = self.device.simctl().spawn(
+            self.device
+                .simctl()
+                .command("io")
+                .arg(&self.device.udid)
+                .arg("recordVideo")
+                .arg(format!("--display={}", display.as_arg()))
+                .arg(format!("--mask={}", mask.as_arg()))
+                .arg("--force")
+                .arg(path),
+        )?;
+
+        Ok(VideoRecording {
+            path: path.to_path_buf(),
+            child: Some(child),
+        })
+    }
+
+    /// Takes a screenshot of the given display, with the given mask and writes
+    /// it to the given path, encoded using the given type. If no type is given,
+    /// it is inferred from the extension of the path.
+    pub fn screenshot_to_file(
+        &self,
+        path: &Path,
+        image_type: Option<ImageType>,
+        display: Display,
+        mask: Mask,
+    ) -> Result<()> {
+        let image_type = match image_type {
+            Some(image_type) => image_type,
+            None => ImageType::from_path(path)?,
+        };
+
+        self.device
+            .simctl()
+            .command("io")
+            .arg(&self.device.udid)
+            .arg("screenshot")
+            .arg(format!("--type={}", image_type.as_arg()))
+            .arg(format!("--display={}", display.as_arg()))
+            .arg(format!("--mask={}", mask.as_arg()))
+            .arg(path)
+            .execute(self.device.simctl())?
+            .validate()
+    }
+
+    /// Returns a boolean that indicates if the `simctl` utility of this Xcode
+    /// installation supports sending hardware keys (i.e. if `simctl help io`
+    /// lists the `sendkey` operation). Most versions of Xcode don't. The
+    /// result is obtained once and cached afterwards (also for clones of the
+    /// wrapper).
+    pub fn supports_send_key(&self) -> Result<bool> {
+        let simctl = self.device.simctl();
+
+        if let Some(supported) = simctl.supports_send_key.get() {
+            return Ok(*supported);
+        }
+
+        let output = self
+            .device
+            .simctl()
+            .command("help")
+            .arg("io")
+            .stdout(Stdio::piped())
+            .execute(self.device.simctl())?;
+
+        let output = output.validate_with_output()?;
+
+        let supported = String::from_utf8(output.stdout)?.contains("sendkey")
+            || String::from_utf8(output.stderr)?.contains("sendkey");
+
+        let _ = simctl.supports_send_key.set(supported);
+
+        Ok(supported)
+    }
+
+    /// Presses the given hardware key. Returns [`Error::Unsupported`] if the
+    /// `simctl` utility of this Xcode installation doesn't support sending
+    /// hardware keys (see [`IO::supports_send_key`]).
+    pub fn send_key(&self, key: HardwareKey) -> Result<()> {
+        if !self.supports_send_key()? {
+            return Err(Error::Unsupported(
+                "this version of simctl does not support sending hardware keys".to_owned(),
+            ));
+        }
+
+        self.device
+            .simctl()
+            .command("io")
+            .arg(&self.device.udid)
+            .arg("sendkey")
+            .arg(key.as_arg())
+            .execute(self.device.simctl())?
+            .validate()
+    }
 }
 
 #[cfg(test)]
@@ -145,4 +668,467 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_screenshot_png() -> Result<()> {
+        mock::device()?.boot()?;
+
+        // Wait for the screenshot service to become available (see above).
+        mock::device()?.io().screenshot_with_retry(
+            ImageType::Png,
+            Display::Internal,
+            Mask::Ignored,
+            5,
+            Duration::from_millis(500),
+        )?;
+
+        let png = mock::device()?.screenshot_png()?;
+        assert!(png.starts_with(b"\x89PNG"));
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_screenshot_data_uri() -> Result<()> {
+        mock::device()?.boot()?;
+
+        // Wait for the screenshot service to become available (see above).
+        mock::device()?.io().screenshot_with_retry(
+            ImageType::Png,
+            Display::Internal,
+            Mask::Ignored,
+            5,
+            Duration::from_millis(500),
+        )?;
+
+        let uri = mock::device()?.io().screenshot_data_uri(ImageType::Png)?;
+        assert!(uri.starts_with("data:image/png;base64,iVBORw0KGgo"));
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+        assert_eq!(base64(b"fooba"), "Zm9vYmE=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0xff, 0xfe, 0xfd]), "//79");
+    }
+
+    #[test]
+    #[serial]
+    fn test_screenshot_with_retry() -> Result<()> {
+        mock::device()?.boot()?;
+
+        let bytes = mock::device()?.io().screenshot_with_retry(
+            ImageType::Png,
+            Display::Internal,
+            Mask::Ignored,
+            5,
+            Duration::from_millis(500),
+        )?;
+        assert!(!bytes.is_empty());
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_screenshot_with_info() -> Result<()> {
+        mock::device()?.boot()?;
+
+        // See `test_screenshot` for why we retry.
+        let mut result = None;
+        for i in 0..5 {
+            match mock::device()?
+                .io()
+                .screenshot_with_info(Display::Internal, Mask::Ignored)
+            {
+                Ok((_, info)) => {
+                    result = Some(info);
+                    break;
+                }
+                Err(_) if i < 4 => continue,
+                Err(error) => return Err(error),
+            }
+        }
+
+        mock::device()?.shutdown()?;
+
+        let info = result.unwrap();
+        assert!(info.width > 0);
+        assert!(info.height > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_screenshot_info_from_png() -> Result<()> {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend_from_slice(&[0, 0, 0, 13]);
+        png.extend_from_slice(b"IHDR");
+        png.extend_from_slice(&1170u32.to_be_bytes());
+        png.extend_from_slice(&2532u32.to_be_bytes());
+        png.extend_from_slice(&[8, 6, 0, 0, 0]);
+        png.extend_from_slice(&[0; 4]);
+        png.extend_from_slice(&[0, 0, 0, 9]);
+        png.extend_from_slice(b"pHYs");
+        png.extend_from_slice(&8504u32.to_be_bytes());
+        png.extend_from_slice(&8504u32.to_be_bytes());
+        png.push(1);
+        png.extend_from_slice(&[0; 4]);
+
+        assert_eq!(
+            ScreenshotInfo::from_png(&png)?,
+            ScreenshotInfo {
+                width: 1170,
+                height: 2532,
+                scale: Some(3.0),
+            }
+        );
+
+        assert!(ScreenshotInfo::from_png(b"GIF89a").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_screenshot_to_file() -> Result<()> {
+        let path = std::env::temp_dir().join("simctl_screenshot.png");
+
+        mock::device()?.boot()?;
+
+        // See `test_screenshot` for why we retry.
+        for i in 0..5 {
+            match mock::device()?.io().screenshot_to_file(
+                &path,
+                None,
+                Display::Internal,
+                Mask::Ignored,
+            ) {
+                Ok(_) => break,
+                Err(_) if i < 4 => continue,
+                Err(error) => return Err(error),
+            }
+        }
+
+        mock::device()?.shutdown()?;
+
+        let bytes = std::fs::read(&path)?;
+        assert!(bytes.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        std::fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_record_video() -> Result<()> {
+        let path = std::env::temp_dir().join("simctl_recording.mp4");
+
+        mock::device()?.boot()?;
+
+        let recording =
+            mock::device()?
+                .io()
+                .record_video(&path, Display::Internal, Mask::Ignored)?;
+        thread::sleep(Duration::from_secs(2));
+        recording.stop_and_wait(Duration::from_secs(10))?;
+
+        mock::device()?.shutdown()?;
+
+        // MP4 files start with an `ftyp` box.
+        let bytes = std::fs::read(&path)?;
+        assert_eq!(bytes.get(4..8), Some(&b"ftyp"[..]));
+
+        std::fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_video_stop_and_wait() -> Result<()> {
+        // Both stubs create a marker file once their trap is installed, so the
+        // interrupt can't arrive before the stub is able to handle it.
+        fn wait_until_ready(path: &Path) {
+            let marker = path.with_extension("ready");
+            while !marker.exists() {
+                thread::sleep(Duration::from_millis(10));
+            }
+            let _ = std::fs::remove_file(marker);
+        }
+
+        let path = std::env::temp_dir().join("simctl_stub_recording.mp4");
+        let _ = std::fs::remove_file(&path);
+
+        // This stub only writes the file once it's interrupted, just like the
+        // real CLI finalizes the recording when it receives SIGINT.
+        let simctl = mock::stub(
+            "record_video",
+            "trap 'echo recording > \"$7\"; exit 0' INT\n\
+             : > \"${7%.mp4}.ready\"\n\
+             while :; do sleep 0.1; done",
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+        let recording = device
+            .io()
+            .record_video(&path, Display::Internal, Mask::Ignored)?;
+        wait_until_ready(&path);
+        recording.stop_and_wait(Duration::from_secs(5))?;
+        assert_eq!(std::fs::read_to_string(&path)?, "recording\n");
+
+        std::fs::remove_file(&path)?;
+
+        let simctl = mock::stub(
+            "record_video_hang",
+            "trap '' INT\n: > \"${7%.mp4}.ready\"\nwhile :; do sleep 0.1; done",
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+        let recording = device
+            .io()
+            .record_video(&path, Display::Internal, Mask::Ignored)?;
+        wait_until_ready(&path);
+        assert!(matches!(
+            recording.stop_and_wait(Duration::from_millis(300)),
+            Err(Error::Timeout)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_send_key() -> Result<()> {
+        if !mock::device()?.io().supports_send_key()? {
+            return Ok(());
+        }
+
+        mock::device()?.boot()?;
+        mock::device()?.io().send_key(HardwareKey::Home)?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_send_key_unsupported() -> Result<()> {
+        let simctl = mock::stub(
+            "send_key_unsupported",
+            "echo 'Usage: simctl io <device> <operation> <arguments>'",
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        assert!(!device.io().supports_send_key()?);
+        assert!(matches!(
+            device.io().send_key(HardwareKey::Home),
+            Err(Error::Unsupported(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_send_key_stub() -> Result<()> {
+        let log = std::env::temp_dir().join("simctl_stub_send_key.log");
+        let _ = std::fs::remove_file(&log);
+
+        let script = format!(
+            r#"echo "$@" >> '{}'
+case "$1" in
+help) echo 'sendkey <key>  Presses a hardware key.' ;;
+io) [ "$3 $4" = "sendkey home" ] || exit 1 ;;
+esac"#,
+            log.display()
+        );
+
+        let simctl = mock::stub("send_key", &script)?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        device.io().send_key(HardwareKey::Home)?;
+        device.io().send_key(HardwareKey::Home)?;
+        assert_eq!(
+            std::fs::read_to_string(&log)?,
+            "help io\n\
+             io 2C7A3E5E-5C2C-4B37-9C1C-6F0A4C1A1E01 sendkey home\n\
+             io 2C7A3E5E-5C2C-4B37-9C1C-6F0A4C1A1E01 sendkey home\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_image_type_from_path() {
+        assert_eq!(
+            ImageType::from_path(Path::new("a.png")).ok(),
+            Some(ImageType::Png)
+        );
+        assert_eq!(
+            ImageType::from_path(Path::new("a.JPG")).ok(),
+            Some(ImageType::Jpeg)
+        );
+        assert!(ImageType::from_path(Path::new("a")).is_err());
+    }
+
+    #[test]
+    fn test_image_type_from_str() {
+        assert_eq!(ImageType::from_extension("jpg").ok(), Some(ImageType::Jpeg));
+        assert_eq!("jpeg".parse::<ImageType>().ok(), Some(ImageType::Jpeg));
+        assert_eq!(ImageType::try_from("PNG").ok(), Some(ImageType::Png));
+        assert!(ImageType::from_extension("webp").is_err());
+        assert!("".parse::<ImageType>().is_err());
+    }
+
+    const ENUMERATE: &str = "\
+Port:
+    UUID: 1E6C2C6B-7A4F-4D39-9B1E-2F1C3E0A5D11
+    Power state: On
+    Display class: 0
+    Port Identifier: com.apple.framebuffer.display
+    Class: Display
+    Default width: 750
+    Default height: 1334
+Port:
+    UUID: 5B2D8F0E-3C1A-4E7B-A6D9-8F4C2B1E7A22
+    Port Identifier: com.apple.hid.keyboard
+    Class: Keyboard
+";
+
+    const ENUMERATE_EXTERNAL: &str = "\
+Port:
+    UUID: 9C4E1A7D-2B5F-4A8C-B3E6-1D7F9A2C4E33
+    Power state: On
+    Display class: 1
+    Port Identifier: com.apple.framebuffer.display.external
+    Class: Display
+    Default width: 1920
+    Default height: 1080
+";
+
+    /// Returns a stub script that prints the given output when the I/O ports
+    /// of a device are enumerated and otherwise runs the given script.
+    fn with_enumerate(output: &str, script: &str) -> String {
+        format!(
+            "if [ \"$1 $3\" = \"io enumerate\" ]; then\ncat <<'EOF'\n{}EOF\nexit 0\nfi\n{}",
+            output, script
+        )
+    }
+
+    #[test]
+    fn test_parse_displays() {
+        assert_eq!(parse_displays(ENUMERATE), vec![Display::Internal]);
+        assert_eq!(
+            parse_displays(&format!("{}{}", ENUMERATE, ENUMERATE_EXTERNAL)),
+            vec![Display::Internal, Display::External]
+        );
+        assert_eq!(parse_displays(""), vec![]);
+    }
+
+    #[test]
+    fn test_screenshot_skips_validation() -> Result<()> {
+        // Screenshots must not enumerate the displays, which this stub
+        // rejects.
+        let simctl = mock::stub(
+            "screenshot",
+            "[ \"$3 $6\" = \"screenshot --mask=alpha\" ] || exit 1\nprintf png",
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        let bytes = device
+            .io()
+            .screenshot(ImageType::Png, Display::Internal, Mask::Alpha)?;
+        assert_eq!(bytes, b"png");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_display() -> Result<()> {
+        let simctl = mock::stub("enumerate", &with_enumerate(ENUMERATE, "exit 1"))?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        assert!(device.io().supports_display(Display::Internal)?);
+        assert!(!device.io().supports_display(Display::External)?);
+        assert!(device
+            .io()
+            .validate_display(Display::Internal, Mask::Ignored)
+            .is_ok());
+        assert!(matches!(
+            device
+                .io()
+                .validate_display(Display::External, Mask::Ignored),
+            Err(Error::Unsupported(_))
+        ));
+
+        let simctl = mock::stub(
+            "enumerate_external",
+            &with_enumerate(&format!("{}{}", ENUMERATE, ENUMERATE_EXTERNAL), "exit 1"),
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        assert!(device.io().supports_display(Display::External)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_mask() -> Result<()> {
+        // Point the device types at profiles with and without a mask.
+        let root = std::env::temp_dir().join("simctl_profiles");
+        let json = include_str!("../tests/list.json")
+            .replace(
+                "\\/Applications\\/Xcode.app\\/Contents\\/Developer\\/Platforms\\/iPhoneOS.platform\\/Library\\/Developer\\/CoreSimulator\\/Profiles\\/DeviceTypes",
+                &root.display().to_string(),
+            );
+
+        for (name, has_mask) in &[("iPhone SE (2nd generation)", false), ("iPhone 12", true)] {
+            let resources = root.join(format!("{}.simdevicetype/Contents/Resources", name));
+            std::fs::create_dir_all(&resources)?;
+
+            let mut profile = plist::Dictionary::new();
+            if *has_mask {
+                profile.insert("framebufferMask".to_owned(), "Mask".into());
+            }
+
+            plist::to_file_xml(resources.join("profile.plist"), &profile)?;
+        }
+
+        std::fs::write(root.join("list.json"), json)?;
+
+        let simctl = mock::stub(
+            "profiles",
+            &with_enumerate(
+                ENUMERATE,
+                &format!(
+                    "[ \"$1\" = list ] || exit 1\ncat \"{}\"",
+                    root.join("list.json").display()
+                ),
+            ),
+        )?;
+        let se = mock::fixture_device_with(simctl.clone(), "iPhone SE (2nd generation)")?;
+        let notched = mock::fixture_device_with(simctl, "iPhone 12")?;
+
+        assert!(se.io().supports_mask(Mask::Ignored)?);
+        assert!(!se.io().supports_mask(Mask::Alpha)?);
+        assert!(notched.io().supports_mask(Mask::Black)?);
+        assert!(matches!(
+            se.io().validate_display(Display::Internal, Mask::Alpha),
+            Err(Error::Unsupported(_))
+        ));
+
+        Ok(())
+    }
 }
@@ -1,8 +1,11 @@
 //! Supporting types for the `simctl keychain` subcommand.
 
-use super::{Device, Result, Validate};
+use std::path::Path;
+
+use super::{ensure_exists, Device, Execute, Result, Validate};
 
 /// Wrapper around the `simctl keychain` subcommand.
+#[derive(Clone, Debug)]
 pub struct Keychain {
     device: Device,
 }
@@ -24,7 +27,37 @@ impl Keychain {
             .command("keychain")
             .arg(&self.device.udid)
             .arg("reset")
-            .output()?
+            .execute(self.device.simctl())?
+            .validate()
+    }
+
+    /// Adds the certificate at the given path to the device's keychain.
+    pub fn add_cert(&self, path: &Path) -> Result<()> {
+        ensure_exists(path)?;
+
+        self.device
+            .simctl()
+            .command("keychain")
+            .arg(&self.device.udid)
+            .arg("add-cert")
+            .arg(path)
+            .execute(self.device.simctl())?
+            .validate()
+    }
+
+    /// Adds the certificate at the given path to the device's keychain as a
+    /// trusted root. This is useful to trust a local certificate authority
+    /// (e.g. to mock HTTPS endpoints).
+    pub fn add_root_cert(&self, path: &Path) -> Result<()> {
+        ensure_exists(path)?;
+
+        self.device
+            .simctl()
+            .command("keychain")
+            .arg(&self.device.udid)
+            .arg("add-root-cert")
+            .arg(path)
+            .execute(self.device.simctl())?
             .validate()
     }
 }
@@ -45,4 +78,18 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_keychain_add_root_cert() -> Result<()> {
+        let mut path = Path::new(env!("CARGO_MANIFEST_DIR")).to_path_buf();
+        path.push("tests/Example.pem");
+
+        mock::device()?.boot()?;
+        mock::device()?.keychain().add_root_cert(&path)?;
+        mock::device()?.keychain().reset()?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
 }
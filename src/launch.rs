@@ -1,11 +1,88 @@
 //! Supporting types for the `simctl launch` subcommand.
 
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
 use std::fmt::Display;
+use std::io::ErrorKind;
 use std::path::Path;
-use std::process::Stdio;
+use std::process::{Child, ChildStderr, ChildStdout, Command, ExitStatus, Stdio};
 
-use super::{Device, Result, Validate};
+#[cfg(feature = "tokio")]
+use super::ExecuteAsync;
+use super::{Device, Error, Execute, Result, Validate};
+
+/// Architecture that an app can be launched as.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Arch {
+    /// Indicates the native architecture of Apple Silicon.
+    Arm64,
+
+    /// Indicates the Intel architecture. On Apple Silicon, apps that are
+    /// launched as this architecture are translated by Rosetta.
+    X86_64,
+}
+
+impl Arch {
+    pub(crate) fn as_arg(&self) -> &'static str {
+        match self {
+            Arch::Arm64 => "arm64",
+            Arch::X86_64 => "x86_64",
+        }
+    }
+}
+
+/// Handle to an app that was launched with [`Launch::spawn`]. The app's
+/// console output can be read through [`LaunchSession::stdout`] and
+/// [`LaunchSession::stderr`]. When this handle is dropped, the app is killed
+/// (see [`LaunchSession::kill`]).
+#[derive(Debug)]
+pub struct LaunchSession {
+    device: Device,
+    bundle_id: String,
+    child: Child,
+    stdout: ChildStdout,
+    stderr: ChildStderr,
+}
+
+impl LaunchSession {
+    /// Returns a reader for everything the app writes to stdout.
+    pub fn stdout(&mut self) -> &mut ChildStdout {
+        &mut self.stdout
+    }
+
+    /// Returns a reader for everything the app writes to stderr.
+    pub fn stderr(&mut self) -> &mut ChildStderr {
+        &mut self.stderr
+    }
+
+    /// Waits for the app to exit and returns the exit status of the CLI that
+    /// is attached to its console.
+    pub fn wait(&mut self) -> Result<ExitStatus> {
+        Ok(self.child.wait()?)
+    }
+
+    /// Terminates the app and kills the CLI that is attached to its console.
+    /// This does nothing if the app has already exited.
+    pub fn kill(&mut self) -> Result<()> {
+        if self.child.try_wait()?.is_some() {
+            return Ok(());
+        }
+
+        // The app may have exited in the meantime, in which case terminating
+        // it fails. That's fine because the CLI is killed regardless.
+        let _ = self.device.terminate(&self.bundle_id);
+
+        let _ = self.child.kill();
+        self.child.wait()?;
+
+        Ok(())
+    }
+}
+
+impl Drop for LaunchSession {
+    fn drop(&mut self) {
+        let _ = self.kill();
+    }
+}
 
 /// Builder that can be used to customize the launch of an application.
 #[derive(Debug)]
@@ -13,11 +90,14 @@ pub struct Launch<'a> {
     device: Device,
     bundle_id: &'a str,
     wait_for_debugger: bool,
+    terminate_running_process: bool,
     use_pty: Option<bool>,
+    arch: Option<Arch>,
     stdout: Option<&'a Path>,
     stderr: Option<&'a Path>,
     args: Vec<&'a OsStr>,
     envs: Vec<(String, &'a OsStr)>,
+    env_clear: bool,
 }
 
 impl<'a> Launch<'a> {
@@ -27,6 +107,14 @@ impl<'a> Launch<'a> {
         self
     }
 
+    /// Indicates whether a running instance of the application should be
+    /// terminated before launching it again. Otherwise, launching an app that
+    /// is already running has no effect.
+    pub fn terminate_running_process(&mut self, terminate: bool) -> &mut Launch<'a> {
+        self.terminate_running_process = terminate;
+        self
+    }
+
     /// Indicates whether the output should be written to a console with PTY.
     pub fn use_pty(&mut self, use_pty: bool) -> &mut Launch<'a> {
         self.use_pty = Some(use_pty);
@@ -35,6 +123,14 @@ impl<'a> Launch<'a> {
         self
     }
 
+    /// Launches the application as the given architecture (e.g. to run an
+    /// app with Intel-only dependencies on Apple Silicon). By default, the
+    /// app is launched as the native architecture.
+    pub fn arch(&mut self, arch: Arch) -> &mut Launch<'a> {
+        self.arch = Some(arch);
+        self
+    }
+
     /// Writes stdout to the given path.
     pub fn stdout<P>(&mut self, path: &'a P) -> &mut Launch<'a>
     where
@@ -68,21 +164,143 @@ impl<'a> Launch<'a> {
     pub fn env<K, V>(&mut self, key: K, value: &'a V) -> &mut Launch<'a>
     where
         K: Display,
-        V: AsRef<OsStr>,
+        V: AsRef<OsStr> + ?Sized,
     {
         self.envs
             .push((format!("SIMCTL_CHILD_{}", key), value.as_ref()));
         self
     }
 
+    /// Adds each of the given environment variables to the program (e.g. from
+    /// a `HashMap`). See [`Launch::env`].
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Launch<'a>
+    where
+        I: IntoIterator<Item = (K, &'a V)>,
+        K: Display,
+        V: AsRef<OsStr> + ?Sized + 'a,
+    {
+        for (key, value) in vars {
+            self.env(key, value);
+        }
+
+        self
+    }
+
+    /// Adds an environment variable that will be passed as-is to `simctl`
+    /// itself. In contrast to [`Launch::env`], the key is not prefixed with
+    /// `SIMCTL_CHILD_`. Only variables that `simctl` recognizes (or that
+    /// already carry the `SIMCTL_CHILD_` prefix, e.g.
+    /// `SIMCTL_CHILD_DYLD_INSERT_LIBRARIES`) have an effect.
+    pub fn raw_env<K, V>(&mut self, key: K, value: &'a V) -> &mut Launch<'a>
+    where
+        K: Display,
+        V: AsRef<OsStr>,
+    {
+        self.envs.push((key.to_string(), value.as_ref()));
+        self
+    }
+
+    /// Prevents `SIMCTL_CHILD_*` variables that happen to be set on the host
+    /// from leaking into the launched app. The rest of the environment (e.g.
+    /// `HOME` and `DEVELOPER_DIR`) is still inherited by `simctl` itself,
+    /// which needs it to locate the device set. Variables that are added
+    /// through [`Launch::env`] or [`Launch::raw_env`] are still passed on,
+    /// regardless of whether they were added before or after calling this.
+    pub fn env_clear(&mut self) -> &mut Launch<'a> {
+        self.env_clear = true;
+        self
+    }
+
     /// Executes the launch.
     pub fn exec(&mut self) -> Result<()> {
+        self.command().execute(self.device.simctl())?.validate()
+    }
+
+    /// Executes the launch without attaching to the console of the app and
+    /// returns its PID. Unlike [`Launch::exec`] with a console, this does not
+    /// wait for the app to exit. Any previous calls to [`Launch::use_pty`],
+    /// [`Launch::stdout`] and [`Launch::stderr`] are overridden.
+    pub(crate) fn exec_pid(&mut self) -> Result<u32> {
+        self.use_pty = None;
+        self.stdout = None;
+        self.stderr = None;
+
+        let output = self
+            .command()
+            .stdout(Stdio::piped())
+            .execute(self.device.simctl())?;
+
+        let output = output.validate_with_output()?;
+
+        parse_pid(&String::from_utf8(output.stdout)?)
+    }
+
+    /// Executes the launch with a console PTY and returns everything the app
+    /// wrote to its console (i.e. both stdout and stderr), without the
+    /// `<bundle id>: <pid>` line that the CLI prints first. This blocks until
+    /// the app exits. Any previous calls to [`Launch::use_pty`],
+    /// [`Launch::stdout`] and [`Launch::stderr`] are overridden.
+    pub fn exec_capture(&mut self) -> Result<String> {
+        self.use_pty(true);
+
+        let output = self
+            .command()
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .execute(self.device.simctl())?;
+
+        let output = output.validate_with_output()?;
+        let stdout = String::from_utf8(output.stdout)?;
+
+        Ok(strip_pid_line(&stdout, self.bundle_id).to_owned())
+    }
+
+    /// Launches the app with its console attached and returns a handle that
+    /// can be used to read its output and to kill it. Unlike
+    /// [`Launch::exec`], this does not wait for the app to exit. Any previous
+    /// calls to [`Launch::use_pty`], [`Launch::stdout`] and [`Launch::stderr`]
+    /// are overridden.
+    pub fn spawn(&mut self) -> Result<LaunchSession> {
+        self.use_pty(false);
+
+        let mut child = self
+            .device
+            .simctl()
+            .spawn(self.command().stdout(Stdio::piped()).stderr(Stdio::piped()))?;
+
+        // Both streams were piped above, so they are always present.
+        let stdout = child.stdout.take().unwrap();
+        let stderr = child.stderr.take().unwrap();
+
+        Ok(LaunchSession {
+            device: self.device.clone(),
+            bundle_id: self.bundle_id.to_owned(),
+            child,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Asynchronous variant of [`Launch::exec`].
+    #[cfg(feature = "tokio")]
+    pub async fn exec_async(&mut self) -> Result<()> {
+        tokio::process::Command::from(self.command())
+            .execute_async(self.device.simctl())
+            .await?
+            .validate()
+    }
+
+    fn command(&self) -> Command {
         let mut command = self.device.simctl().command("launch");
 
         if self.wait_for_debugger {
             command.arg("--wait-for-debugger");
         }
 
+        if self.terminate_running_process {
+            command.arg("--terminate-running-process");
+        }
+
         if let Some(use_pty) = self.use_pty {
             match use_pty {
                 true => command.arg("--console-pty"),
@@ -90,6 +308,10 @@ impl<'a> Launch<'a> {
             };
         }
 
+        if let Some(arch) = self.arch {
+            command.arg(format!("--arch={}", arch.as_arg()));
+        }
+
         if let Some(stdout) = self.stdout {
             command.arg(format!("--stdout={}", stdout.display()));
         } else {
@@ -102,6 +324,10 @@ impl<'a> Launch<'a> {
             command.stderr(Stdio::inherit());
         }
 
+        if self.env_clear {
+            remove_child_envs(&mut command, std::env::vars_os());
+        }
+
         command.envs(self.envs.iter().map(|(k, v)| (k, v)));
 
         command.arg(&self.device.udid);
@@ -109,7 +335,49 @@ impl<'a> Launch<'a> {
 
         command.args(&self.args);
 
-        command.output()?.validate()
+        command
+    }
+}
+
+/// Parses the PID from the output of `simctl launch`, which is formatted as
+/// `<bundle id>: <pid>`.
+fn parse_pid(stdout: &str) -> Result<u32> {
+    stdout
+        .trim()
+        .rsplit(": ")
+        .next()
+        .and_then(|pid| pid.parse().ok())
+        .ok_or_else(|| {
+            Error::Io(std::io::Error::new(
+                ErrorKind::InvalidData,
+                format!("unexpected output of simctl launch: {}", stdout),
+            ))
+        })
+}
+
+/// Strips the first line of the given console output of `simctl launch` if it
+/// is the `<bundle id>: <pid>` line that the CLI prints before the output of
+/// the app itself.
+fn strip_pid_line<'a>(stdout: &'a str, bundle_id: &str) -> &'a str {
+    let (first, rest) = stdout.split_once('\n').unwrap_or((stdout, ""));
+
+    match first.starts_with(bundle_id) && parse_pid(first).is_ok() {
+        true => rest,
+        false => stdout,
+    }
+}
+
+/// Removes each of the given (inherited) variables that `simctl` would
+/// otherwise forward to the launched app (i.e. those with the `SIMCTL_CHILD_`
+/// prefix) from the given command.
+fn remove_child_envs<I>(command: &mut Command, vars: I)
+where
+    I: IntoIterator<Item = (OsString, OsString)>,
+{
+    for (key, _) in vars {
+        if key.to_string_lossy().starts_with("SIMCTL_CHILD_") {
+            command.env_remove(key);
+        }
     }
 }
 
@@ -121,17 +389,23 @@ impl Device {
             device: self.clone(),
             bundle_id,
             wait_for_debugger: false,
+            terminate_running_process: false,
             use_pty: Some(false),
+            arch: None,
             stdout: None,
             stderr: None,
             args: vec![],
             envs: vec![],
+            env_clear: false,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read};
+
     use super::*;
     use crate::mock;
 
@@ -154,4 +428,254 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_launch_terminate_running_process() -> Result<()> {
+        mock::device()?.boot()?;
+
+        let path = "/dev/zero";
+
+        mock::device()?
+            .launch("com.apple.mobilesafari")
+            .stdout(&path)
+            .stderr(&path)
+            .exec()?;
+
+        mock::device()?
+            .launch("com.apple.mobilesafari")
+            .terminate_running_process(true)
+            .stdout(&path)
+            .stderr(&path)
+            .exec()?;
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_launch_arch() -> Result<()> {
+        mock::device()?.boot()?;
+
+        let path = "/dev/zero";
+        let arch = match cfg!(target_arch = "aarch64") {
+            true => Arch::Arm64,
+            false => Arch::X86_64,
+        };
+
+        mock::device()?
+            .launch("com.apple.mobilesafari")
+            .arch(arch)
+            .stdout(&path)
+            .stderr(&path)
+            .exec()?;
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_launch_raw_env() -> Result<()> {
+        mock::device()?.boot()?;
+
+        let path = "/dev/zero";
+
+        mock::device()?
+            .launch("com.apple.mobilesafari")
+            .raw_env("SIMCTL_CHILD_TEST_VAR", &"Hello World!")
+            .stdout(&path)
+            .stderr(&path)
+            .exec()?;
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_launch_raw_env_stub() -> Result<()> {
+        let simctl = mock::stub("raw_env", "exec /usr/bin/env")?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        let output = device
+            .launch("com.apple.mobilesafari")
+            .raw_env("DYLD_PRINT_STATISTICS", &"1")
+            .env("TEST_VAR", "Hello World!")
+            .exec_capture()?;
+
+        let lines = output.lines().collect::<Vec<_>>();
+        assert!(lines.contains(&"DYLD_PRINT_STATISTICS=1"));
+        assert!(lines.contains(&"SIMCTL_CHILD_TEST_VAR=Hello World!"));
+        assert!(!lines.contains(&"SIMCTL_CHILD_DYLD_PRINT_STATISTICS=1"));
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_launch_envs() -> Result<()> {
+        mock::device()?.boot()?;
+
+        let path = "/dev/zero";
+
+        let mut vars = HashMap::new();
+        vars.insert("FIRST", "1");
+        vars.insert("SECOND", "2");
+        vars.insert("THIRD", "3");
+
+        mock::device()?
+            .launch("com.apple.mobilesafari")
+            .envs(&vars)
+            .stdout(&path)
+            .stderr(&path)
+            .exec()?;
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_launch_env_clear() -> Result<()> {
+        let simctl = mock::stub("env_clear", "exec /usr/bin/env")?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        let output = device
+            .launch("com.apple.mobilesafari")
+            .env("BEFORE", "1")
+            .env_clear()
+            .env("AFTER", "2")
+            .exec_capture()?;
+
+        let mut vars = output
+            .lines()
+            .filter(|line| line.starts_with("SIMCTL_CHILD_"))
+            .collect::<Vec<_>>();
+        vars.sort();
+        assert_eq!(vars, vec!["SIMCTL_CHILD_AFTER=2", "SIMCTL_CHILD_BEFORE=1"]);
+
+        if let Some(path) = std::env::var_os("PATH") {
+            let path = format!("PATH={}", path.to_string_lossy());
+            assert!(output.lines().any(|line| line == path));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_pid() {
+        assert_eq!(parse_pid("com.apple.mobilesafari: 4242\n").ok(), Some(4242));
+        assert!(parse_pid("").is_err());
+        assert!(parse_pid("com.apple.mobilesafari: launching").is_err());
+    }
+
+    #[test]
+    fn test_strip_pid_line() {
+        let bundle_id = "com.glacyr.Example";
+        assert_eq!(
+            strip_pid_line("com.glacyr.Example: 1234\nHello World!\n", bundle_id),
+            "Hello World!\n"
+        );
+        assert_eq!(strip_pid_line("com.glacyr.Example: 1234", bundle_id), "");
+        assert_eq!(strip_pid_line("Hello: 1234\n", bundle_id), "Hello: 1234\n");
+        assert_eq!(strip_pid_line("", bundle_id), "");
+    }
+
+    #[test]
+    fn test_remove_child_envs() {
+        let mut command = Command::new("env");
+        remove_child_envs(
+            &mut command,
+            vec![
+                ("HOME".into(), "/Users/test".into()),
+                ("SIMCTL_CHILD_LEAKED".into(), "1".into()),
+            ],
+        );
+
+        assert_eq!(
+            command.get_envs().collect::<Vec<_>>(),
+            vec![(OsStr::new("SIMCTL_CHILD_LEAKED"), None)]
+        );
+    }
+
+    #[test]
+    fn test_launch_exec_capture() -> Result<()> {
+        // Like the CLI, the stub prints the pid of the app, followed by the
+        // output of the app itself.
+        let simctl = mock::stub(
+            "exec_capture",
+            "[ \"$2\" = \"--console-pty\" ] || exit 1\n\
+             echo \"$4: 1234\"\n\
+             echo \"Hello World!\"",
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        let output = device.launch("com.glacyr.Example").exec_capture()?;
+        assert_eq!(output, "Hello World!\n");
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_launch_spawn() -> Result<()> {
+        mock::device()?.boot()?;
+
+        let mut session = mock::device()?.launch("com.apple.mobilesafari").spawn()?;
+
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        session.kill()?;
+
+        let mut stderr = String::new();
+        session.stderr().read_to_string(&mut stderr)?;
+        assert!(session.wait().is_ok());
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_launch_spawn_stub() -> Result<()> {
+        let simctl = mock::stub(
+            "launch_spawn",
+            "if [ \"$1\" = launch ]; then echo 'Hello World!' >&2; exec sleep 10; fi",
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        let mut session = device.launch("com.glacyr.simctl.Example").spawn()?;
+
+        let mut line = String::new();
+        BufReader::new(session.stderr()).read_line(&mut line)?;
+        assert_eq!(line, "Hello World!\n");
+
+        session.kill()?;
+        assert!(!session.wait()?.success());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    #[serial]
+    async fn test_launch_async() -> Result<()> {
+        mock::device()?.boot_async().await?;
+
+        let path = "/dev/zero";
+
+        mock::device()?
+            .launch("com.apple.mobilesafari")
+            .stdout(&path)
+            .stderr(&path)
+            .exec_async()
+            .await?;
+
+        mock::device()?.shutdown_async().await?;
+
+        Ok(())
+    }
 }
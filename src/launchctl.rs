@@ -0,0 +1,69 @@
+use std::process::{Output, Stdio};
+
+use super::{Device, Execute, Result, Validate};
+
+impl Device {
+    /// Runs `launchctl` with the given arguments on this device and returns
+    /// its output if it completes successfully. This can be used to control
+    /// the background services of the simulator.
+    pub fn launchctl(&self, args: &[&str]) -> Result<Output> {
+        self.simctl()
+            .command("spawn")
+            .arg(&self.udid)
+            .arg("launchctl")
+            .args(args)
+            .stdout(Stdio::piped())
+            .execute(self.simctl())?
+            .validate_with_output()
+    }
+
+    /// Sets an environment variable in the `launchd` of this device (i.e.
+    /// `launchctl setenv`), which is inherited by processes that are started
+    /// afterwards. The variable can be read back with [`Device::getenv`].
+    pub fn launchctl_setenv(&self, key: &str, value: &str) -> Result<()> {
+        self.launchctl(&["setenv", key, value])?;
+        Ok(())
+    }
+
+    /// Returns a description of the `launchd` system domain of this device
+    /// (i.e. `launchctl print system`), which includes all of its services.
+    /// The format of this description is not stable across versions.
+    pub fn launchctl_print(&self) -> Result<String> {
+        let output = self.launchctl(&["print", "system"])?;
+        Ok(String::from_utf8(output.stdout)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    fn test_launchctl_setenv() -> Result<()> {
+        mock::device()?.boot()?;
+
+        mock::device()?.launchctl_setenv("TEST_VAR", "Hello World!")?;
+        assert_eq!(mock::device()?.getenv("TEST_VAR")?, "Hello World!");
+        assert!(!mock::device()?.launchctl_print()?.is_empty());
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_launchctl_stub() -> Result<()> {
+        let simctl = mock::stub("launchctl", r#"shift 2; echo "$@""#)?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        let output = device.launchctl(&["setenv", "TEST_VAR", "1"])?;
+        assert_eq!(output.stdout, b"launchctl setenv TEST_VAR 1\n");
+        assert_eq!(device.launchctl_print()?, "launchctl print system\n");
+
+        Ok(())
+    }
+}
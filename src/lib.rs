@@ -1,34 +1,58 @@
 //! Rust-wrapper around the `simctl` utility that is shipped with Xcode and that
 //! can be used to install apps onto one of the iOS simulator and subsequently
 //! launch them.
+//!
+//! All operations are blocking by default. If the `tokio` feature is enabled,
+//! asynchronous variants (suffixed with `_async`) of the most common operations
+//! are available as well. Both APIs can be used side-by-side.
 
 #![warn(missing_docs)]
 
 mod device;
 mod simctl;
 
-mod boot;
+mod app_info;
+pub mod boot;
+mod boot_status;
+mod clone;
+mod create;
+pub mod diagnose;
 mod error;
 pub mod get_app_container;
 mod getenv;
+mod help;
+mod icloud_sync;
 mod install;
 pub mod io;
 pub mod keychain;
 pub mod launch;
+mod launchctl;
 pub mod list;
+pub mod list_apps;
+pub mod location;
+mod log_stream;
+mod log_verbose;
+mod notify_post;
 mod open_url;
+mod pasteboard;
 pub mod privacy;
 pub mod push;
+pub mod runtime;
 mod shutdown;
 pub mod status_bar;
 mod terminate;
 pub mod ui;
 mod uninstall;
+pub mod xcode;
 
 #[cfg(test)]
 pub mod mock;
 
 pub use crate::simctl::Simctl;
 pub use device::{Device, DeviceQuery};
-pub(crate) use error::Validate;
+pub(crate) use error::{ensure_exists, Validate};
 pub use error::{Error, Result};
+pub use install::bundle_id_of;
+pub(crate) use simctl::Execute;
+#[cfg(feature = "tokio")]
+pub(crate) use simctl::ExecuteAsync;
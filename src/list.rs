@@ -1,28 +1,105 @@
 //! Supporting types for the `simctl list` subcommand.
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
 use std::path::PathBuf;
 use std::process::Stdio;
+use std::str::FromStr;
 
-use super::{Device, Result, Simctl};
+#[cfg(feature = "tokio")]
+use super::ExecuteAsync;
+use super::{Device, Execute, Result, Simctl};
 
-/// Indicates the state of a device.
-#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+/// Indicates the state of a device. States are ordered by how far the device
+/// is up (i.e. `Creating < Shutdown < ShuttingDown < Booting < Booted`), so
+/// that polling code can check if a device is at least in a given state. An
+/// unknown state can't be compared to other states.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum DeviceState {
     /// Indicates that the device is booted.
     Booted,
 
+    /// Indicates that the device is booting (i.e. it will be booted soon).
+    Booting,
+
+    /// Indicates that the device is being created (i.e. it will be shutdown
+    /// soon).
+    Creating,
+
     /// Indicates that the device is shutdown.
     Shutdown,
 
+    /// Indicates that the device is shutting down (i.e. it will be shutdown
+    /// soon).
+    #[serde(rename = "Shutting Down")]
+    ShuttingDown,
+
     /// Indicates that the device is in an unknown state.
     #[serde(other)]
     Unknown,
 }
 
+impl FromStr for DeviceState {
+    type Err = Infallible;
+
+    /// Parses the given device state case-insensitively (e.g. `booted`). Any
+    /// state that is not recognized is parsed as [`DeviceState::Unknown`].
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "booted" => DeviceState::Booted,
+            "booting" => DeviceState::Booting,
+            "creating" => DeviceState::Creating,
+            "shutdown" => DeviceState::Shutdown,
+            "shutting down" => DeviceState::ShuttingDown,
+            _ => DeviceState::Unknown,
+        })
+    }
+}
+
+impl DeviceState {
+    /// Returns the position of this state in the order that is described in
+    /// the documentation of [`DeviceState`].
+    fn rank(&self) -> Option<u8> {
+        match self {
+            DeviceState::Creating => Some(0),
+            DeviceState::Shutdown => Some(1),
+            DeviceState::ShuttingDown => Some(2),
+            DeviceState::Booting => Some(3),
+            DeviceState::Booted => Some(4),
+            DeviceState::Unknown => None,
+        }
+    }
+}
+
+impl PartialOrd for DeviceState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.rank(), other.rank()) {
+            (Some(rank), Some(other)) => Some(rank.cmp(&other)),
+            _ if self == other => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DeviceState {
+    /// Formats this device state in the same way as the CLI (e.g. `Booted`).
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            DeviceState::Booted => "Booted",
+            DeviceState::Booting => "Booting",
+            DeviceState::Creating => "Creating",
+            DeviceState::Shutdown => "Shutdown",
+            DeviceState::ShuttingDown => "Shutting Down",
+            DeviceState::Unknown => "Unknown",
+        })
+    }
+}
+
 /// Indicates the state of a pair of devices.
-#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum DevicePairState {
     /// Indicates that this pair is unavailable because one of its components is
     /// unavailable.
@@ -40,7 +117,7 @@ pub enum DevicePairState {
 }
 
 /// Information about a device type.
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct DeviceType {
     /// Contains the minimum runtime version that this device type supports.
     /// This is relevant for devices that are newer than the oldest runtime that
@@ -71,8 +148,60 @@ pub struct DeviceType {
     pub product_family: String,
 }
 
+/// Version of a runtime, parsed from its human-readable version string (e.g.
+/// `14.2`). Missing components are zero, so `14` and `14.0.0` are equal.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct RuntimeVersion {
+    /// Contains the major component of this version.
+    pub major: u32,
+
+    /// Contains the minor component of this version.
+    pub minor: u32,
+
+    /// Contains the patch component of this version.
+    pub patch: u32,
+}
+
+impl RuntimeVersion {
+    /// Parses the given dotted version string (e.g. `17.0.1`). Only the leading
+    /// digits of each component are used (e.g. `16.4 beta` is parsed as
+    /// `16.4.0`), parsing stops at the first component that doesn't start with
+    /// a digit, and any components after the patch component are ignored, so
+    /// this never fails.
+    pub fn parse(version: &str) -> RuntimeVersion {
+        let mut components = version.trim().split('.').map_while(|component| {
+            let end = component
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(component.len());
+            component[..end].parse::<u32>().ok()
+        });
+
+        RuntimeVersion {
+            major: components.next().unwrap_or_default(),
+            minor: components.next().unwrap_or_default(),
+            patch: components.next().unwrap_or_default(),
+        }
+    }
+
+    /// Unpacks the given version, which is encoded as `major << 16 | minor << 8
+    /// | patch` (see [`DeviceType::min_runtime_version`]).
+    pub fn from_packed(version: usize) -> RuntimeVersion {
+        RuntimeVersion {
+            major: ((version >> 16) & 0xffff) as u32,
+            minor: ((version >> 8) & 0xff) as u32,
+            patch: (version & 0xff) as u32,
+        }
+    }
+}
+
+impl fmt::Display for RuntimeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
 /// Information about a runtime.
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Runtime {
     /// Contains a path to the bundle of this runtime. This is usually not
     /// relevant to end-users.
@@ -108,13 +237,109 @@ pub struct Runtime {
     pub name: String,
 }
 
+impl Runtime {
+    /// Returns the version of this runtime, parsed from [`Runtime::version`].
+    pub fn parsed_version(&self) -> RuntimeVersion {
+        RuntimeVersion::parse(&self.version)
+    }
+}
+
+impl Ord for Runtime {
+    /// Orders runtimes by their parsed version (see
+    /// [`Runtime::parsed_version`]), so that the newest runtime comes last.
+    /// Runtimes with the same version (e.g. iOS 14.2 and tvOS 14.2) are
+    /// ordered by their identifier.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.parsed_version()
+            .cmp(&other.parsed_version())
+            .then_with(|| self.identifier.cmp(&other.identifier))
+            .then_with(|| self.version.cmp(&other.version))
+            .then_with(|| self.build_version.cmp(&other.build_version))
+            .then_with(|| self.name.cmp(&other.name))
+            .then_with(|| self.is_available.cmp(&other.is_available))
+            .then_with(|| self.bundle_path.cmp(&other.bundle_path))
+            .then_with(|| self.runtime_root.cmp(&other.runtime_root))
+    }
+}
+
+impl PartialOrd for Runtime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl DeviceType {
+    /// Returns a boolean that indicates if devices of this type can be created
+    /// with the given runtime, i.e. if the runtime is for the platform of this
+    /// device type (e.g. iOS for iPhones) and its version is within
+    /// [`DeviceType::min_runtime_version`] and
+    /// [`DeviceType::max_runtime_version`]. This does not check if the runtime
+    /// is available.
+    pub fn supports_runtime(&self, runtime: &Runtime) -> bool {
+        let platform = match self.product_family.as_str() {
+            "iPhone" | "iPad" => Some("iOS"),
+            "Apple Watch" => Some("watchOS"),
+            "Apple TV" => Some("tvOS"),
+            _ => None,
+        };
+
+        if let Some(platform) = platform {
+            if !runtime.identifier.contains(&format!(".{}-", platform)) {
+                return false;
+            }
+        }
+
+        let version = runtime.parsed_version();
+
+        version >= RuntimeVersion::from_packed(self.min_runtime_version)
+            && version <= RuntimeVersion::from_packed(self.max_runtime_version)
+    }
+}
+
+/// Reason why a device is unavailable, classified from the free-form
+/// [`DeviceInfo::availability_error`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AvailabilityError {
+    /// Indicates that the runtime of the device is no longer installed (e.g.
+    /// because it was shipped with an older version of Xcode). Installing the
+    /// runtime makes the device available again.
+    RuntimeProfileNotFound,
+
+    /// Indicates that the device type of the device is no longer supported by
+    /// this version of Xcode.
+    DeviceTypeProfileNotFound,
+
+    /// Indicates that the runtime of the device is installed but unavailable.
+    RuntimeUnavailable,
+
+    /// This is returned for errors that are not (yet) recognized by this
+    /// library. Contains the original error.
+    Other(String),
+}
+
+impl AvailabilityError {
+    fn parse(error: &str) -> AvailabilityError {
+        let lowercase = error.to_lowercase();
+
+        if lowercase.contains("runtime profile not found") {
+            AvailabilityError::RuntimeProfileNotFound
+        } else if lowercase.contains("device type profile not found") {
+            AvailabilityError::DeviceTypeProfileNotFound
+        } else if lowercase.contains("runtime") && lowercase.contains("unavailable") {
+            AvailabilityError::RuntimeUnavailable
+        } else {
+            AvailabilityError::Other(error.to_owned())
+        }
+    }
+}
+
 /// Information about a device.
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct DeviceInfo {
     /// Note: this field is not directly present in JSON. Instead, the JSON
     /// representation is a hashmap of runtime IDs (keys) and devices (values)
     /// that we later connect during deserialization.
-    #[serde(skip_deserializing)]
+    #[serde(skip)]
     pub runtime_identifier: String,
 
     /// If this device is not available (see [`DeviceInfo::is_available`]), this
@@ -151,8 +376,33 @@ pub struct DeviceInfo {
     pub name: String,
 }
 
+impl DeviceInfo {
+    /// Returns a boolean that indicates if this device was booted when it was
+    /// retrieved. Use [`Device::is_currently_booted`] to query its current
+    /// state instead.
+    pub fn is_booted(&self) -> bool {
+        self.state == DeviceState::Booted
+    }
+
+    /// Returns a boolean that indicates if this device was shutdown when it
+    /// was retrieved. Use [`Device::is_currently_shutdown`] to query its
+    /// current state instead.
+    pub fn is_shutdown(&self) -> bool {
+        self.state == DeviceState::Shutdown
+    }
+
+    /// Returns the reason why this device is unavailable, classified from
+    /// [`DeviceInfo::availability_error`]. Returns `None` if there is no
+    /// availability error.
+    pub fn availability_reason(&self) -> Option<AvailabilityError> {
+        self.availability_error
+            .as_deref()
+            .map(AvailabilityError::parse)
+    }
+}
+
 /// Short summary of a device that is used as part of a device pair.
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct DeviceSummary {
     /// Contains the name of this device.
     pub name: String,
@@ -165,12 +415,12 @@ pub struct DeviceSummary {
 }
 
 /// Information about a device pair.
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct DevicePair {
     /// Note: this field is not directly present in JSON. Instead, the JSON
     /// representation is a hashmap of runtime IDs (keys) and devices (values)
     /// that we later connect during deserialization.
-    #[serde(skip_deserializing)]
+    #[serde(skip)]
     pub udid: String,
 
     /// Contains a summary of the watch device.
@@ -187,6 +437,7 @@ pub struct DevicePair {
 #[derive(Debug)]
 pub struct List {
     simctl: Simctl,
+    search: Option<String>,
     device_types: Vec<DeviceType>,
     runtimes: Vec<Runtime>,
     devices: Vec<Device>,
@@ -194,13 +445,30 @@ pub struct List {
 }
 
 impl List {
-    /// Refreshes the `simctl list` subcommand's output.
+    /// Refreshes the `simctl list` subcommand's output. If this list was
+    /// obtained with [`Simctl::list_filtered`], the same search term is used.
     pub fn refresh(&mut self) -> Result<()> {
         let mut command = self.simctl.command("list");
         command.arg("-j");
+        command.args(&self.search);
+        command.stdout(Stdio::piped());
+        let output = command.execute(&self.simctl)?;
+        self.update(&output.stdout)
+    }
+
+    /// Asynchronous variant of [`List::refresh`].
+    #[cfg(feature = "tokio")]
+    pub async fn refresh_async(&mut self) -> Result<()> {
+        let mut command = self.simctl.command_async("list");
+        command.arg("-j");
+        command.args(&self.search);
         command.stdout(Stdio::piped());
-        let output = command.output()?;
-        let output: ListOutput = serde_json::from_slice(&output.stdout)?;
+        let output = command.execute_async(&self.simctl).await?;
+        self.update(&output.stdout)
+    }
+
+    fn update(&mut self, stdout: &[u8]) -> Result<()> {
+        let output: ListOutput = serde_json::from_slice(stdout)?;
         self.device_types = output.device_types;
         self.runtimes = output.runtimes;
         self.devices = output
@@ -229,6 +497,16 @@ impl List {
         Ok(())
     }
 
+    /// Returns a list from a JSON document that was previously obtained by
+    /// serializing a list (or from the output of `xcrun simctl list -j`),
+    /// without invoking `simctl`. Devices in the resulting list will use the
+    /// given wrapper for subsequent operations.
+    pub fn from_json(simctl: Simctl, json: &str) -> Result<List> {
+        let mut list = simctl.empty_list();
+        list.update(json.as_bytes())?;
+        Ok(list)
+    }
+
     /// Returns all device types that have been registered with `simctl`.
     pub fn device_types(&self) -> &[DeviceType] {
         &self.device_types
@@ -244,13 +522,79 @@ impl List {
         &self.devices
     }
 
+    /// Returns the device with the given UDID, if it has been registered with
+    /// `simctl`.
+    pub fn device_by_udid(&self, udid: &str) -> Option<&Device> {
+        self.devices.iter().find(|device| device.udid == udid)
+    }
+
+    /// Consumes this list and returns the device with the given UDID, if it has
+    /// been registered with `simctl`.
+    pub fn into_device(self, udid: &str) -> Option<Device> {
+        self.devices.into_iter().find(|device| device.udid == udid)
+    }
+
+    /// Returns the device type of the given device (see
+    /// [`DeviceInfo::device_type_identifier`]), if it is still registered with
+    /// `simctl`.
+    pub fn device_type_of(&self, device: &Device) -> Option<&DeviceType> {
+        self.device_types
+            .iter()
+            .find(|device_type| device_type.identifier == device.device_type_identifier)
+    }
+
+    /// Returns the runtime of the given device (see
+    /// [`DeviceInfo::runtime_identifier`]), if it is still registered with
+    /// `simctl`.
+    pub fn runtime_of(&self, device: &Device) -> Option<&Runtime> {
+        self.runtimes
+            .iter()
+            .find(|runtime| runtime.identifier == device.runtime_identifier)
+    }
+
+    /// Returns all devices that have been registered with `simctl`, grouped
+    /// by their runtime identifier (see [`DeviceInfo::runtime_identifier`]).
+    /// Within each group, devices retain the order of this list.
+    pub fn devices_by_runtime(&self) -> HashMap<String, Vec<&Device>> {
+        let mut groups = HashMap::<String, Vec<&Device>>::new();
+
+        for device in self.devices.iter() {
+            groups
+                .entry(device.runtime_identifier.clone())
+                .or_default()
+                .push(device);
+        }
+
+        groups
+    }
+
+    /// Returns the newest available runtime that supports the given device
+    /// type (see [`DeviceType::supports_runtime`]), if any.
+    pub fn newest_runtime_for(&self, device_type: &DeviceType) -> Option<&Runtime> {
+        self.runtimes
+            .iter()
+            .filter(|runtime| runtime.is_available && device_type.supports_runtime(runtime))
+            .max()
+    }
+
     /// Returns all device pairs that have been registered with `simctl`.
     pub fn pairs(&self) -> &[DevicePair] {
         &self.pairs
     }
+
+    /// Returns the watch device of the given pair.
+    pub fn pair_watch(&self, pair: &DevicePair) -> Option<&Device> {
+        self.device_by_udid(&pair.watch.udid)
+    }
+
+    /// Returns the phone device of the given pair.
+    pub fn pair_phone(&self, pair: &DevicePair) -> Option<&Device> {
+        self.device_by_udid(&pair.phone.udid)
+    }
 }
 
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
 struct ListOutput {
     #[serde(rename = "devicetypes")]
     device_types: Vec<DeviceType>,
@@ -259,25 +603,89 @@ struct ListOutput {
     pairs: HashMap<String, DevicePair>,
 }
 
+impl Serialize for List {
+    /// Serializes this list into the same structure that is returned by `simctl
+    /// list -j`.
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut devices = HashMap::<String, Vec<DeviceInfo>>::new();
+
+        for device in &self.devices {
+            devices
+                .entry(device.runtime_identifier.clone())
+                .or_default()
+                .push(device.info().clone());
+        }
+
+        ListOutput {
+            device_types: self.device_types.clone(),
+            runtimes: self.runtimes.clone(),
+            devices,
+            pairs: self
+                .pairs
+                .iter()
+                .map(|pair| (pair.udid.clone(), pair.clone()))
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
 impl Simctl {
     /// Returns a list of all device types, runtimes, devices and device pairs
     /// that have been registered with `simctl`.
     pub fn list(&self) -> Result<List> {
-        let mut list = List {
+        let mut list = self.empty_list();
+        list.refresh()?;
+        Ok(list)
+    }
+
+    /// Returns a list of the device types, runtimes, devices and device pairs
+    /// that match the given search term (e.g. `iOS 14.2` or `iPhone`). The
+    /// search is performed by the CLI, which is faster than filtering the
+    /// output of [`Simctl::list`] on machines with many devices.
+    pub fn list_filtered(&self, search: &str) -> Result<List> {
+        let mut list = self.empty_list();
+        list.search = Some(search.to_owned());
+        list.refresh()?;
+        Ok(list)
+    }
+
+    /// Asynchronous variant of [`Simctl::list`].
+    #[cfg(feature = "tokio")]
+    pub async fn list_async(&self) -> Result<List> {
+        let mut list = self.empty_list();
+        list.refresh_async().await?;
+        Ok(list)
+    }
+
+    /// Returns all devices that have been registered with `simctl`. This is a
+    /// shortcut for [`Simctl::list`] if you're not interested in device types,
+    /// runtimes or device pairs.
+    pub fn list_devices(&self) -> Result<Vec<Device>> {
+        Ok(self.list()?.devices)
+    }
+
+    fn empty_list(&self) -> List {
+        List {
             simctl: self.clone(),
+            search: None,
             device_types: vec![],
             devices: vec![],
             pairs: vec![],
             runtimes: vec![],
-        };
-        list.refresh()?;
-        Ok(list)
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
+
     use super::*;
+    use crate::mock;
 
     #[test]
     fn test_list() -> Result<()> {
@@ -285,4 +693,379 @@ mod tests {
         let _ = simctl.list()?;
         Ok(())
     }
+
+    #[test]
+    fn test_list_filtered() -> Result<()> {
+        let device = mock::device()?;
+        let list = Simctl::new().list()?;
+        let runtime = list.runtime_of(&device).unwrap();
+
+        let filtered = Simctl::new().list_filtered(&runtime.name)?;
+        assert!(filtered
+            .devices()
+            .iter()
+            .any(|next| next.udid == device.udid));
+        assert!(filtered
+            .devices()
+            .iter()
+            .all(|next| next.runtime_identifier == runtime.identifier));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_filtered_stub() -> Result<()> {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/list.json");
+        let simctl = mock::stub(
+            "list_filtered",
+            &format!(
+                r#"[ "$1 $2 $3" = "list -j iOS 14.2" ] && cat '{}'"#,
+                fixture.display()
+            ),
+        )?;
+
+        assert_eq!(simctl.list_filtered("iOS 14.2")?.devices().len(), 5);
+        assert!(simctl.list().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_devices() -> Result<()> {
+        let udid = mock::device()?.udid.clone();
+        let devices = Simctl::new().list_devices()?;
+        assert!(devices.iter().any(|device| device.udid == udid));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pair_devices() -> Result<()> {
+        let list = Simctl::new().list()?;
+
+        for pair in list.pairs() {
+            assert_eq!(list.pair_watch(pair).unwrap().udid, pair.watch.udid);
+            assert_eq!(list.pair_phone(pair).unwrap().udid, pair.phone.udid);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_json() -> Result<()> {
+        let simctl = Simctl::new();
+        let list = simctl.list()?;
+        let json = serde_json::to_string(&list)?;
+        let restored = List::from_json(simctl, &json)?;
+
+        let mut udids = list
+            .devices()
+            .iter()
+            .map(|device| &device.udid)
+            .collect::<Vec<_>>();
+        let mut restored_udids = restored
+            .devices()
+            .iter()
+            .map(|device| &device.udid)
+            .collect::<Vec<_>>();
+        udids.sort();
+        restored_udids.sort();
+        assert_eq!(udids, restored_udids);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_device_by_udid() -> Result<()> {
+        let udid = mock::device()?.udid.clone();
+
+        let mut list = Simctl::new().list()?;
+        list.refresh()?;
+
+        assert_eq!(list.device_by_udid(&udid).unwrap().udid, udid);
+        assert_eq!(list.into_device(&udid).unwrap().udid, udid);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_device_type_of() -> Result<()> {
+        let device = mock::device()?;
+        let list = Simctl::new().list()?;
+
+        let device_type = list.device_type_of(&device).unwrap();
+        assert_eq!(device_type.identifier, device.device_type_identifier);
+        assert_eq!(device_type.product_family, "iPhone");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_runtime_of() -> Result<()> {
+        let device = mock::device()?;
+        let list = Simctl::new().list()?;
+
+        let runtime = list.runtime_of(&device).unwrap();
+        assert_eq!(runtime.identifier, device.runtime_identifier);
+        assert!(!runtime.version.is_empty());
+        assert!(runtime.is_available);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_devices_by_runtime() -> Result<()> {
+        let device = mock::device()?;
+        let list = Simctl::new().list()?;
+
+        let groups = list.devices_by_runtime();
+        assert!(groups[&device.runtime_identifier]
+            .iter()
+            .any(|next| next.udid == device.udid));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_devices_by_runtime_fixture() -> Result<()> {
+        let list = mock::fixture()?;
+        let groups = list.devices_by_runtime();
+
+        assert_eq!(groups.len(), 4);
+        assert_eq!(
+            groups["com.apple.CoreSimulator.SimRuntime.iOS-14-2"]
+                .iter()
+                .map(|device| device.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["iPhone SE (2nd generation)", "iPhone 12"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_runtime_version_parse() {
+        let version = |major, minor, patch| RuntimeVersion {
+            major,
+            minor,
+            patch,
+        };
+
+        assert_eq!(RuntimeVersion::parse("14.2"), version(14, 2, 0));
+        assert_eq!(RuntimeVersion::parse("17.0.1"), version(17, 0, 1));
+        assert_eq!(RuntimeVersion::parse("17"), RuntimeVersion::parse("17.0.0"));
+        assert_eq!(RuntimeVersion::parse("16.4 beta"), version(16, 4, 0));
+        assert_eq!(RuntimeVersion::parse("16.x"), version(16, 0, 0));
+        assert_eq!(RuntimeVersion::parse(""), RuntimeVersion::default());
+        assert!(RuntimeVersion::parse("16.10") > RuntimeVersion::parse("16.4"));
+    }
+
+    #[test]
+    fn test_runtime_ord() -> Result<()> {
+        let list = mock::fixture()?;
+
+        let mut runtimes = list.runtimes().to_vec();
+        runtimes.sort();
+
+        assert_eq!(
+            runtimes
+                .iter()
+                .map(|runtime| runtime.version.as_str())
+                .collect::<Vec<_>>(),
+            vec!["7.1", "13.0", "14.2", "14.2"]
+        );
+        assert_eq!(runtimes.last(), list.runtimes().iter().max());
+        assert_eq!(
+            runtimes.last().unwrap().identifier,
+            "com.apple.CoreSimulator.SimRuntime.tvOS-14-2"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_runtime_version_from_packed() {
+        assert_eq!(
+            RuntimeVersion::from_packed(851968),
+            RuntimeVersion::parse("13.0")
+        );
+        assert_eq!(
+            RuntimeVersion::from_packed(0x0e0201),
+            RuntimeVersion::parse("14.2.1")
+        );
+    }
+
+    #[test]
+    fn test_newest_runtime_for() -> Result<()> {
+        let device = mock::device()?;
+        let list = Simctl::new().list()?;
+
+        let device_type = list.device_type_of(&device).unwrap();
+        let runtime = list.newest_runtime_for(device_type).unwrap();
+        assert!(runtime.is_available);
+        assert!(
+            runtime.parsed_version()
+                >= RuntimeVersion::from_packed(device_type.min_runtime_version)
+        );
+        assert!(
+            runtime.parsed_version()
+                <= RuntimeVersion::from_packed(device_type.max_runtime_version)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_newest_runtime_for_fixture() -> Result<()> {
+        let list = mock::fixture()?;
+
+        let newest = |name: &str| {
+            let device_type = list
+                .device_types()
+                .iter()
+                .find(|device_type| device_type.name == name)
+                .unwrap();
+            list.newest_runtime_for(device_type)
+                .map(|runtime| runtime.identifier.as_str())
+        };
+
+        assert_eq!(
+            newest("iPhone SE (2nd generation)"),
+            Some("com.apple.CoreSimulator.SimRuntime.iOS-14-2")
+        );
+        assert_eq!(
+            newest("Apple Watch Series 6 - 44mm"),
+            Some("com.apple.CoreSimulator.SimRuntime.watchOS-7-1")
+        );
+        assert_eq!(
+            newest("Apple TV 4K"),
+            Some("com.apple.CoreSimulator.SimRuntime.tvOS-14-2")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_availability_error_parse() {
+        let cases = [
+            (
+                "runtime profile not found",
+                AvailabilityError::RuntimeProfileNotFound,
+            ),
+            (
+                "runtime profile not found using \"System\" match policy",
+                AvailabilityError::RuntimeProfileNotFound,
+            ),
+            (
+                "device type profile not found",
+                AvailabilityError::DeviceTypeProfileNotFound,
+            ),
+            (
+                "The runtime for this device is unavailable",
+                AvailabilityError::RuntimeUnavailable,
+            ),
+            (
+                "something else",
+                AvailabilityError::Other("something else".to_owned()),
+            ),
+        ];
+
+        for (error, expected) in cases.iter() {
+            assert_eq!(&AvailabilityError::parse(error), expected);
+        }
+    }
+
+    #[test]
+    fn test_availability_reason() -> Result<()> {
+        let list = mock::fixture()?;
+
+        let reasons = list
+            .devices()
+            .iter()
+            .filter_map(|device| device.availability_reason())
+            .collect::<Vec<_>>();
+        assert_eq!(reasons, vec![AvailabilityError::RuntimeProfileNotFound]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_device_state_from_str() {
+        for state in [
+            DeviceState::Booted,
+            DeviceState::Shutdown,
+            DeviceState::Unknown,
+        ]
+        .iter()
+        {
+            assert_eq!(state.to_string().parse(), Ok(*state));
+        }
+
+        assert_eq!("booted".parse(), Ok(DeviceState::Booted));
+        assert_eq!("SHUTDOWN".parse(), Ok(DeviceState::Shutdown));
+        assert_eq!("shutting down".parse(), Ok(DeviceState::ShuttingDown));
+        assert_eq!("Erasing".parse(), Ok(DeviceState::Unknown));
+    }
+
+    #[test]
+    fn test_device_state_deserialize_transient() -> Result<()> {
+        let cases = [
+            ("\"Booting\"", DeviceState::Booting),
+            ("\"Shutting Down\"", DeviceState::ShuttingDown),
+            ("\"Creating\"", DeviceState::Creating),
+        ];
+
+        for &(json, state) in cases.iter() {
+            assert_eq!(serde_json::from_str::<DeviceState>(json)?, state);
+            assert_eq!(serde_json::to_string(&state)?, json);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_device_state_ord() {
+        assert!(DeviceState::Creating < DeviceState::Shutdown);
+        assert!(DeviceState::Shutdown < DeviceState::ShuttingDown);
+        assert!(DeviceState::ShuttingDown < DeviceState::Booting);
+        assert!(DeviceState::Booting < DeviceState::Booted);
+        assert!(DeviceState::Booted >= DeviceState::Booting);
+        assert!(DeviceState::Booted >= DeviceState::Booted);
+
+        assert_eq!(
+            DeviceState::Unknown.partial_cmp(&DeviceState::Unknown),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(DeviceState::Unknown.partial_cmp(&DeviceState::Booted), None);
+    }
+
+    #[test]
+    fn test_device_state_serialize() -> Result<()> {
+        let json = serde_json::to_string(&DeviceState::Booted)?;
+        assert_eq!(json, "\"Booted\"");
+        assert_eq!(
+            serde_json::from_str::<DeviceState>(&json)?,
+            DeviceState::Booted
+        );
+
+        assert_eq!(
+            serde_json::from_str::<DeviceState>("\"Booting\"")?,
+            DeviceState::Booting
+        );
+
+        let json = serde_json::to_string(&DeviceState::Unknown)?;
+        assert_eq!(
+            serde_json::from_str::<DeviceState>(&json)?,
+            DeviceState::Unknown
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_list_async() -> Result<()> {
+        let simctl = Simctl::new();
+        let _ = simctl.list_async().await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,213 @@
+//! Supporting types for the `simctl listapps` subcommand.
+
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+
+use super::{Device, Execute, Result, Validate};
+
+/// Indicates whether an app ships with the OS or was installed by the user.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+pub enum ApplicationType {
+    /// Indicates that this app ships with the OS (e.g. Safari).
+    System,
+
+    /// Indicates that this app was installed by the user (e.g. through
+    /// [`Device::install`]).
+    User,
+
+    /// Indicates that this app has a type that is not (yet) recognized by this
+    /// library.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Information about an app that is installed on a device.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct InstalledApp {
+    /// Contains the bundle ID of this app.
+    #[serde(rename = "CFBundleIdentifier")]
+    pub bundle_id: String,
+
+    /// Contains the name of this app that is shown to the user. Some apps do
+    /// not have a display name.
+    #[serde(default, rename = "CFBundleDisplayName")]
+    pub display_name: Option<String>,
+
+    /// Contains the path to the .app bundle of this app.
+    #[serde(rename = "Path")]
+    pub bundle_path: PathBuf,
+
+    /// Contains the path to the data container of this app (also see
+    /// [`crate::get_app_container::Container::Data`]). Some system apps do
+    /// not have a data container.
+    #[serde(
+        default,
+        rename = "DataContainer",
+        deserialize_with = "deserialize_file_url"
+    )]
+    pub data_container: Option<PathBuf>,
+
+    /// Indicates whether this app ships with the OS or was installed by the
+    /// user.
+    #[serde(rename = "ApplicationType")]
+    pub application_type: ApplicationType,
+}
+
+/// Deserializes an optional `file://` URL (as emitted by `simctl`) into a path.
+pub(crate) fn deserialize_file_url<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<PathBuf>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let url = match Option::<String>::deserialize(deserializer)? {
+        Some(url) => url,
+        None => return Ok(None),
+    };
+
+    let path = url.strip_prefix("file://").unwrap_or(&url).as_bytes();
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut i = 0;
+
+    while i < path.len() {
+        let decoded = match path[i] {
+            b'%' => path
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+
+        match decoded {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            None => {
+                bytes.push(path[i]);
+                i += 1;
+            }
+        }
+    }
+
+    Ok(Some(PathBuf::from(
+        String::from_utf8_lossy(&bytes).into_owned(),
+    )))
+}
+
+impl Device {
+    /// Returns all apps that are installed on this device, including system
+    /// apps.
+    pub fn list_apps(&self) -> Result<Vec<InstalledApp>> {
+        let output = self
+            .simctl()
+            .command("listapps")
+            .arg(&self.udid)
+            .stdout(Stdio::piped())
+            .execute(self.simctl())?;
+
+        let output = output.validate_with_output()?;
+
+        let apps: HashMap<String, InstalledApp> = plist::from_reader_ascii(&output.stdout[..])?;
+
+        Ok(apps.into_values().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    fn test_list_apps() -> Result<()> {
+        mock::device()?.boot()?;
+
+        let apps = mock::device()?.list_apps()?;
+        let safari = apps
+            .iter()
+            .find(|app| app.bundle_id == "com.apple.mobilesafari")
+            .unwrap();
+        assert_eq!(safari.application_type, ApplicationType::System);
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+    #[test]
+    fn test_list_apps_stub() -> Result<()> {
+        let simctl = mock::stub(
+            "list_apps",
+            r#"[ "$1 $2" = "listapps 2C7A3E5E-5C2C-4B37-9C1C-6F0A4C1A1E01" ] || exit 1
+cat <<'EOF'
+{
+    "com.apple.mobilesafari" =     {
+        ApplicationType = System;
+        CFBundleDisplayName = Safari;
+        CFBundleIdentifier = "com.apple.mobilesafari";
+        Path = "/Applications/MobileSafari.app";
+    };
+    "com.glacyr.simctl.Example" =     {
+        ApplicationType = User;
+        CFBundleIdentifier = "com.glacyr.simctl.Example";
+        DataContainer = "file:///Users/simctl/Data/My%20App/";
+        Path = "/Users/simctl/Bundle/Example.app";
+    };
+}
+EOF"#,
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        let mut apps = device.list_apps()?;
+        apps.sort_by(|a, b| a.bundle_id.cmp(&b.bundle_id));
+
+        assert_eq!(
+            apps,
+            vec![
+                InstalledApp {
+                    bundle_id: "com.apple.mobilesafari".to_owned(),
+                    display_name: Some("Safari".to_owned()),
+                    bundle_path: "/Applications/MobileSafari.app".into(),
+                    data_container: None,
+                    application_type: ApplicationType::System,
+                },
+                InstalledApp {
+                    bundle_id: "com.glacyr.simctl.Example".to_owned(),
+                    display_name: None,
+                    bundle_path: "/Users/simctl/Bundle/Example.app".into(),
+                    data_container: Some("/Users/simctl/Data/My App/".into()),
+                    application_type: ApplicationType::User,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_file_url() -> std::result::Result<(), serde_json::Error> {
+        let decode = |url: &str| deserialize_file_url(serde_json::Value::from(url));
+
+        assert_eq!(
+            decode("file:///Users/simctl/My%20App/")?,
+            Some("/Users/simctl/My App/".into())
+        );
+        assert_eq!(
+            decode("file:///Users/simctl/%C3%A9t%C3%A9/%E2%9C%93")?,
+            Some("/Users/simctl/\u{e9}t\u{e9}/\u{2713}".into())
+        );
+        assert_eq!(decode("file:///100%")?, Some("/100%".into()));
+        assert_eq!(decode("file:///a%2")?, Some("/a%2".into()));
+        assert_eq!(decode("file:///a%zz/b")?, Some("/a%zz/b".into()));
+        assert_eq!(decode("file:///a%FFb")?, Some("/a\u{fffd}b".into()));
+        assert_eq!(decode("/already/a/path")?, Some("/already/a/path".into()));
+        assert_eq!(deserialize_file_url(serde_json::Value::Null)?, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+//! Supporting types for the `simctl location` subcommand.
+
+use super::{Device, Error, Execute, Result, Validate};
+
+/// Wrapper around the `simctl location` subcommand.
+#[derive(Clone, Debug)]
+pub struct Location {
+    device: Device,
+}
+
+impl Device {
+    /// Returns a wrapper around the `simctl location` subcommand.
+    pub fn location(&self) -> Location {
+        Location {
+            device: self.clone(),
+        }
+    }
+}
+
+fn coordinate(latitude: f64, longitude: f64) -> String {
+    format!("{},{}", latitude, longitude)
+}
+
+impl Location {
+    /// Sets the simulated location of this device to the given coordinate.
+    pub fn set(&self, latitude: f64, longitude: f64) -> Result<()> {
+        self.device
+            .simctl()
+            .command("location")
+            .arg(&self.device.udid)
+            .arg("set")
+            .arg("--")
+            .arg(coordinate(latitude, longitude))
+            .execute(self.device.simctl())?
+            .validate()
+    }
+
+    /// Stops any running scenario and clears the simulated location of this
+    /// device.
+    pub fn clear(&self) -> Result<()> {
+        self.device
+            .simctl()
+            .command("location")
+            .arg(&self.device.udid)
+            .arg("clear")
+            .execute(self.device.simctl())?
+            .validate()
+    }
+
+    /// Runs the scenario with the given name (e.g. "Freeway Drive"). Run
+    /// `xcrun simctl location <udid> list` for a list of available scenarios.
+    pub fn run_scenario(&self, name: &str) -> Result<()> {
+        self.device
+            .simctl()
+            .command("location")
+            .arg(&self.device.udid)
+            .arg("run")
+            .arg(name)
+            .execute(self.device.simctl())?
+            .validate()
+    }
+
+    /// Starts moving the simulated location of this device along the given
+    /// waypoints (pairs of latitude and longitude). If a speed is given (in
+    /// meters per second), it is used instead of the default speed. At least
+    /// two waypoints are required: returns [`Error::InvalidArgument`] without
+    /// invoking the CLI otherwise.
+    pub fn start_route(&self, waypoints: &[(f64, f64)], speed: Option<f64>) -> Result<()> {
+        if waypoints.len() < 2 {
+            return Err(Error::InvalidArgument(format!(
+                "a route requires at least 2 waypoints (got {})",
+                waypoints.len()
+            )));
+        }
+
+        let mut command = self.device.simctl().command("location");
+
+        command.arg(&self.device.udid).arg("start");
+
+        if let Some(speed) = speed {
+            command.arg(format!("--speed={}", speed));
+        }
+
+        command.arg("--");
+        command.args(
+            waypoints
+                .iter()
+                .map(|&(latitude, longitude)| coordinate(latitude, longitude)),
+        );
+
+        command.execute(self.device.simctl())?.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    fn test_location() -> Result<()> {
+        mock::device()?.boot()?;
+        mock::device()?.location().set(52.3676, 4.9041)?;
+        mock::device()?.location().clear()?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+    #[test]
+    fn test_location_stub() -> Result<()> {
+        let log = std::env::temp_dir().join("simctl_stub_location.log");
+        let _ = std::fs::remove_file(&log);
+
+        let simctl = mock::stub("location", &format!("echo \"$@\" >> '{}'", log.display()))?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        device.location().set(-33.8688, 151.2093)?;
+        device
+            .location()
+            .start_route(&[(52.3676, 4.9041), (52.37, -4.9)], Some(12.5))?;
+        assert!(matches!(
+            device.location().start_route(&[(52.3676, 4.9041)], None),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        assert_eq!(
+            std::fs::read_to_string(&log)?,
+            "location 2C7A3E5E-5C2C-4B37-9C1C-6F0A4C1A1E01 set -- -33.8688,151.2093\n\
+             location 2C7A3E5E-5C2C-4B37-9C1C-6F0A4C1A1E01 start --speed=12.5 -- 52.3676,4.9041 52.37,-4.9\n"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use std::process::{Child, Stdio};
+
+use super::{Device, Result};
+
+impl Device {
+    /// Starts streaming the unified log of this device (in syslog style) and
+    /// returns the child process, whose stdout can be read line by line. Only
+    /// messages that match the given predicate are streamed, if any (e.g.
+    /// `subsystem == "com.example.app"`). The stream continues until the child
+    /// is killed.
+    pub fn log_stream(&self, predicate: Option<&str>) -> Result<Child> {
+        let mut command = self.simctl().command("spawn");
+        command
+            .arg(&self.udid)
+            .arg("log")
+            .arg("stream")
+            .arg("--style")
+            .arg("syslog");
+
+        if let Some(predicate) = predicate {
+            command.arg("--predicate").arg(predicate);
+        }
+
+        command.stdout(Stdio::piped()).stderr(Stdio::inherit());
+
+        self.simctl().spawn(&mut command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+    use std::io::{BufRead, BufReader};
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    fn test_log_stream() -> Result<()> {
+        mock::device()?.boot()?;
+
+        let mut child = mock::device()?.log_stream(None)?;
+
+        let mut line = String::new();
+        let stdout = child.stdout.take().unwrap();
+        BufReader::new(stdout).read_line(&mut line)?;
+        assert!(!line.is_empty());
+
+        child.kill()?;
+        child.wait()?;
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+}
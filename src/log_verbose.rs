@@ -0,0 +1,34 @@
+use super::{Device, Execute, Result, Validate};
+
+impl Device {
+    /// Enables or disables verbose logging of CoreSimulator for this device.
+    /// This is useful before reproducing a bug that you want to report.
+    pub fn set_log_verbose(&self, enabled: bool) -> Result<()> {
+        self.simctl()
+            .command("logverbose")
+            .arg(&self.udid)
+            .arg(match enabled {
+                true => "enable",
+                false => "disable",
+            })
+            .execute(self.simctl())?
+            .validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    fn test_set_log_verbose() -> Result<()> {
+        mock::device()?.set_log_verbose(true)?;
+        mock::device()?.set_log_verbose(false)?;
+
+        Ok(())
+    }
+}
@@ -1,13 +1,84 @@
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use super::list::List;
 use super::{Device, DeviceQuery, Result, Simctl};
 
+/// Returns the device that tests which require a real installation of Xcode
+/// run on. The device is selected by name only once, after which it's looked
+/// up by its UDID, so that every test uses the same device even if multiple
+/// devices share its name.
 pub fn device() -> Result<Device> {
-    Ok(Simctl::new()
-        .list()?
+    static UDID: OnceLock<String> = OnceLock::new();
+
+    let list = Simctl::new().list()?;
+    let udid = UDID.get_or_init(|| {
+        list.devices()
+            .iter()
+            .available()
+            .by_name("iPhone SE (2nd generation)")
+            .next()
+            .unwrap()
+            .udid
+            .clone()
+    });
+
+    Ok(list.devices().iter().by_udid(udid).next().unwrap().clone())
+}
+
+/// Returns a list that is read from `tests/list.json` rather than from a real
+/// installation of Xcode. Devices in this list use a wrapper that points to a
+/// nonexistent developer dir, so they can only be used to test logic that
+/// doesn't invoke the CLI.
+pub fn fixture() -> Result<List> {
+    fixture_with(Simctl::with_developer_dir(Path::new("/nonexistent")))
+}
+
+/// Returns a list that is read from `tests/list.json`, with devices that use
+/// the given wrapper (e.g. one that is returned by [`stub`]).
+pub fn fixture_with(simctl: Simctl) -> Result<List> {
+    List::from_json(simctl, include_str!("../tests/list.json"))
+}
+
+/// Returns the available device with the given name from [`fixture`].
+pub fn fixture_device(name: &str) -> Result<Device> {
+    first_available(fixture()?, name)
+}
+
+/// Returns the available device with the given name from [`fixture_with`].
+pub fn fixture_device_with(simctl: Simctl, name: &str) -> Result<Device> {
+    first_available(fixture_with(simctl)?, name)
+}
+
+fn first_available(list: List, name: &str) -> Result<Device> {
+    Ok(list
         .devices()
         .iter()
         .available()
-        .by_name("iPhone SE (2nd generation)")
+        .by_name(name)
         .next()
         .unwrap()
         .clone())
 }
+
+/// Returns a wrapper that invokes a shell script with the given body rather
+/// than the real `simctl` binary. The script is written to the temporary
+/// directory under a name that is derived from the given name, so tests that
+/// run in parallel should use distinct names.
+pub fn stub(name: &str, script: &str) -> Result<Simctl> {
+    let path = std::env::temp_dir().join(format!("simctl_stub_{}", name));
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", script))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+
+    Ok(Simctl::with_binary(&path))
+}
+
+/// Returns a command that takes much longer to finish than any of the timeouts
+/// that are used in tests.
+pub fn slow_command() -> Command {
+    let mut command = Command::new("sleep");
+    command.arg("10");
+    command
+}
@@ -0,0 +1,33 @@
+use super::{Device, Execute, Result, Validate};
+
+impl Device {
+    /// Posts a Darwin notification with the given name on this device. Apps
+    /// can observe these notifications (e.g. with `notify_register_dispatch`)
+    /// to trigger test hooks without interacting with their UI.
+    pub fn post_notification(&self, name: &str) -> Result<()> {
+        self.simctl()
+            .command("notify_post")
+            .arg(&self.udid)
+            .arg(name)
+            .execute(self.simctl())?
+            .validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    fn test_post_notification() -> Result<()> {
+        mock::device()?.boot()?;
+        mock::device()?.post_notification("com.glacyr.simctl.Example")?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+}
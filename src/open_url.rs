@@ -1,4 +1,27 @@
-use super::{Device, Result, Validate};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{Device, Error, Execute, Result, Validate};
+
+/// Returns an error if the given URL does not start with a valid scheme (e.g.
+/// `https:` or `maps:`).
+fn validate_scheme(url: &str) -> Result<()> {
+    let scheme = url.split(':').next().unwrap_or_default();
+
+    let valid = url.contains(':')
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+
+    match valid {
+        true => Ok(()),
+        false => Err(Error::InvalidArgument(format!(
+            "URL does not start with a valid scheme: {}",
+            url
+        ))),
+    }
+}
 
 impl Device {
     /// Opens the given URL on this device.
@@ -7,9 +30,47 @@ impl Device {
             .command("openurl")
             .arg(&self.udid)
             .arg(path)
-            .output()?
+            .execute(self.simctl())?
             .validate()
     }
+
+    /// Opens the given URL on this device and waits until the app with the
+    /// given bundle ID (i.e. the app that is expected to handle the URL) is
+    /// running. Returns [`Error::Timeout`] if the app is not running after the
+    /// given timeout.
+    pub fn open_url_and_wait(&self, url: &str, bundle_id: &str, timeout: Duration) -> Result<()> {
+        validate_scheme(url)?;
+
+        self.open_url(url)?;
+
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if self.is_app_running(bundle_id)? {
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout);
+            }
+
+            thread::sleep(Duration::from_millis(250));
+        }
+    }
+
+    /// Returns a boolean that indicates if the app with the given bundle ID has
+    /// a running process on this device (see [`Device::running_apps`]).
+    fn is_app_running(&self, bundle_id: &str) -> Result<bool> {
+        Ok(self.running_apps()?.iter().any(|app| app == bundle_id))
+    }
+
+    /// Opens the given URL on this device. In contrast to
+    /// [`Device::open_url`], the URL has already been parsed (and therefore
+    /// validated) by the caller.
+    #[cfg(feature = "url")]
+    pub fn open_url_typed(&self, url: &url::Url) -> Result<()> {
+        self.open_url(url.as_str())
+    }
 }
 
 #[cfg(test)]
@@ -28,4 +89,44 @@ mod tests {
 
         Ok(())
     }
+
+    #[cfg(feature = "url")]
+    #[test]
+    #[serial]
+    fn test_open_url_typed() -> Result<()> {
+        let https = url::Url::parse("https://www.glacyr.com/").unwrap();
+        let deep_link = url::Url::parse("customscheme://path?query=1").unwrap();
+
+        mock::device()?.boot()?;
+        mock::device()?.open_url_typed(&https)?;
+        // No app handles this scheme, so the CLI is expected to fail here.
+        let _ = mock::device()?.open_url_typed(&deep_link);
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_open_url_and_wait() -> Result<()> {
+        mock::device()?.boot()?;
+        mock::device()?.open_url_and_wait(
+            "maps://?q=Amsterdam",
+            "com.apple.Maps",
+            Duration::from_secs(30),
+        )?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_scheme() {
+        assert!(validate_scheme("https://www.glacyr.com/").is_ok());
+        assert!(validate_scheme("maps://?q=Amsterdam").is_ok());
+        assert!(validate_scheme("x-callback-url://action").is_ok());
+        assert!(validate_scheme("://missing").is_err());
+        assert!(validate_scheme("www.glacyr.com").is_err());
+        assert!(validate_scheme("1password://").is_err());
+    }
 }
@@ -0,0 +1,66 @@
+use std::io::Write;
+use std::process::Stdio;
+
+use super::{Device, Execute, Result, Simctl, Validate};
+
+impl Device {
+    /// Copies the given data to the pasteboard of this device.
+    pub fn pbcopy(&self, data: &[u8]) -> Result<()> {
+        let mut process = self.simctl().spawn(
+            self.simctl()
+                .command("pbcopy")
+                .arg(&self.udid)
+                .stdin(Stdio::piped()),
+        )?;
+
+        if let Some(stdin) = process.stdin.as_mut() {
+            stdin.write_all(data)?;
+        }
+
+        self.simctl().wait_with_output(process)?.validate()
+    }
+
+    /// Returns the contents of the pasteboard of this device.
+    pub fn pbpaste(&self) -> Result<Vec<u8>> {
+        let output = self
+            .simctl()
+            .command("pbpaste")
+            .arg(&self.udid)
+            .stdout(Stdio::piped())
+            .execute(self.simctl())?;
+
+        let output = output.validate_with_output()?;
+
+        Ok(output.stdout)
+    }
+}
+
+impl Simctl {
+    /// Synchronizes the pasteboard of one device to another.
+    pub fn pbsync(&self, from: &Device, to: &Device) -> Result<()> {
+        self.command("pbsync")
+            .arg(&from.udid)
+            .arg(&to.udid)
+            .execute(self)?
+            .validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    #[serial]
+    fn test_pbcopy_pbpaste() -> Result<()> {
+        mock::device()?.boot()?;
+        mock::device()?.pbcopy(b"Hello World!")?;
+        assert_eq!(mock::device()?.pbpaste()?, b"Hello World!");
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+}
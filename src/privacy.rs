@@ -1,10 +1,13 @@
 //! Supporting types for the `simctl privacy` subcommand.
 
-use super::{Device, Result, Validate};
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Device, Error, Execute, Result, Validate};
 
 /// Refers to a specific service that an app needs to have permission for to
 /// access.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum PrivacyService {
     /// Wildcard that includes all services.
     All,
@@ -45,11 +48,27 @@ pub enum PrivacyService {
 
     /// Grants access to Siri.
     Siri,
+
+    /// Grants access to the user's camera.
+    Camera,
+
+    /// Grants permission to show notifications.
+    Notifications,
+
+    /// Grants access to Face ID.
+    FaceId,
+
+    /// Grants network access to a custom keyboard.
+    KeyboardNetwork,
+
+    /// Refers to a service that is not (yet) recognized by this library. The
+    /// contained string is passed to the CLI as-is.
+    Other(String),
 }
 
-impl ToString for PrivacyService {
-    fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for PrivacyService {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
             PrivacyService::All => "all",
             PrivacyService::Calendar => "calendar",
             PrivacyService::ContactsLimited => "contacts-limited",
@@ -63,12 +82,52 @@ impl ToString for PrivacyService {
             PrivacyService::Motion => "motion",
             PrivacyService::Reminders => "reminders",
             PrivacyService::Siri => "siri",
-        }
-        .to_owned()
+            PrivacyService::Camera => "camera",
+            PrivacyService::Notifications => "notifications",
+            PrivacyService::FaceId => "faceid",
+            PrivacyService::KeyboardNetwork => "keyboard-network",
+            PrivacyService::Other(service) => service,
+        })
+    }
+}
+
+impl FromStr for PrivacyService {
+    type Err = Error;
+
+    /// Parses a service from the same tokens that are used by the CLI (e.g.
+    /// `location-always`). Returns an error for unknown tokens. Use
+    /// [`PrivacyService::Other`] to pass unknown services to the CLI anyway.
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "all" => PrivacyService::All,
+            "calendar" => PrivacyService::Calendar,
+            "contacts-limited" => PrivacyService::ContactsLimited,
+            "contacts" => PrivacyService::Contacts,
+            "location" => PrivacyService::Location,
+            "location-always" => PrivacyService::LocationAlways,
+            "photos-add" => PrivacyService::PhotosAdd,
+            "photos" => PrivacyService::Photos,
+            "media-library" => PrivacyService::MediaLibrary,
+            "microphone" => PrivacyService::Microphone,
+            "motion" => PrivacyService::Motion,
+            "reminders" => PrivacyService::Reminders,
+            "siri" => PrivacyService::Siri,
+            "camera" => PrivacyService::Camera,
+            "notifications" => PrivacyService::Notifications,
+            "faceid" => PrivacyService::FaceId,
+            "keyboard-network" => PrivacyService::KeyboardNetwork,
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "unknown privacy service: {}",
+                    s
+                )))
+            }
+        })
     }
 }
 
 /// Wrapper around the `simctl privacy` subcommand.
+#[derive(Clone, Debug)]
 pub struct Privacy {
     device: Device,
 }
@@ -93,7 +152,7 @@ impl Privacy {
             .arg("grant")
             .arg(service.to_string())
             .arg(bundle_id)
-            .output()?
+            .execute(self.device.simctl())?
             .validate()
     }
 
@@ -107,7 +166,7 @@ impl Privacy {
             .arg("revoke")
             .arg(service.to_string())
             .arg(bundle_id)
-            .output()?
+            .execute(self.device.simctl())?
             .validate()
     }
 
@@ -122,10 +181,44 @@ impl Privacy {
             .arg("reset")
             .arg(service.to_string())
             .arg(bundle_id)
-            .output()?
+            .execute(self.device.simctl())?
             .validate()
     }
 
+    /// Grants access to each of the given services to an application with the
+    /// given bundle ID. Stops at the first service that could not be granted
+    /// and returns an [`Error::Multiple`] that contains that service and its
+    /// error.
+    pub fn grant_all(&self, services: &[PrivacyService], bundle_id: &str) -> Result<()> {
+        services.iter().try_for_each(|service| {
+            self.grant(service.clone(), bundle_id)
+                .map_err(|error| Error::Multiple(vec![(service.to_string(), error)]))
+        })
+    }
+
+    /// Revokes access to each of the given services from an application with
+    /// the given bundle ID. Stops at the first service that could not be
+    /// revoked and returns an [`Error::Multiple`] that contains that service
+    /// and its error.
+    pub fn revoke_all(&self, services: &[PrivacyService], bundle_id: &str) -> Result<()> {
+        services.iter().try_for_each(|service| {
+            self.revoke(service.clone(), bundle_id)
+                .map_err(|error| Error::Multiple(vec![(service.to_string(), error)]))
+        })
+    }
+
+    /// Resets access to each of the given services from an application with
+    /// the given bundle ID. Stops at the first service that could not be
+    /// reset and returns an [`Error::Multiple`] that contains that service and
+    /// its error. Not to be confused with [`Privacy::reset_all`], which resets
+    /// access to a single service for all applications.
+    pub fn reset_services(&self, services: &[PrivacyService], bundle_id: &str) -> Result<()> {
+        services.iter().try_for_each(|service| {
+            self.reset(service.clone(), bundle_id)
+                .map_err(|error| Error::Multiple(vec![(service.to_string(), error)]))
+        })
+    }
+
     /// Resets access to the given service from all applications running on the
     /// device.
     pub fn reset_all(&self, service: PrivacyService) -> Result<()> {
@@ -135,7 +228,7 @@ impl Privacy {
             .arg(&self.device.udid)
             .arg("reset")
             .arg(service.to_string())
-            .output()?
+            .execute(self.device.simctl())?
             .validate()
     }
 }
@@ -200,4 +293,103 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_privacy_grant_all() -> Result<()> {
+        let services = [
+            PrivacyService::Location,
+            PrivacyService::Photos,
+            PrivacyService::Contacts,
+        ];
+
+        mock::device()?.boot()?;
+        mock::device()?
+            .privacy()
+            .grant_all(&services, "com.apple.Maps")?;
+        mock::device()?
+            .privacy()
+            .reset_services(&services, "com.apple.Maps")?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_privacy_grant_all_stub() -> Result<()> {
+        let simctl = mock::stub(
+            "privacy_grant_all",
+            "[ \"$3\" = \"grant\" ] && [ \"$4\" != \"photos\" ] || {\n\
+                 echo \"Failed to grant access to $4\" >&2\n\
+                 exit 1\n\
+             }",
+        )?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        let services = [
+            PrivacyService::Location,
+            PrivacyService::Photos,
+            PrivacyService::Contacts,
+        ];
+
+        match device.privacy().grant_all(&services, "com.apple.Maps") {
+            Err(Error::Multiple(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].0, "photos");
+                assert_eq!(
+                    errors[0].1.simctl_message(),
+                    Some("Failed to grant access to photos")
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_privacy_service_to_string() {
+        assert_eq!(PrivacyService::Camera.to_string(), "camera");
+        assert_eq!(PrivacyService::Notifications.to_string(), "notifications");
+        assert_eq!(PrivacyService::FaceId.to_string(), "faceid");
+        assert_eq!(
+            PrivacyService::KeyboardNetwork.to_string(),
+            "keyboard-network"
+        );
+        assert_eq!(
+            PrivacyService::Other("bluetooth".to_owned()).to_string(),
+            "bluetooth"
+        );
+    }
+
+    #[test]
+    fn test_privacy_service_from_str() -> Result<()> {
+        let services = [
+            PrivacyService::All,
+            PrivacyService::Calendar,
+            PrivacyService::ContactsLimited,
+            PrivacyService::Contacts,
+            PrivacyService::Location,
+            PrivacyService::LocationAlways,
+            PrivacyService::PhotosAdd,
+            PrivacyService::Photos,
+            PrivacyService::MediaLibrary,
+            PrivacyService::Microphone,
+            PrivacyService::Motion,
+            PrivacyService::Reminders,
+            PrivacyService::Siri,
+            PrivacyService::Camera,
+            PrivacyService::Notifications,
+            PrivacyService::FaceId,
+            PrivacyService::KeyboardNetwork,
+        ];
+
+        for service in services.iter() {
+            assert_eq!(&PrivacyService::from_str(&service.to_string())?, service);
+        }
+
+        assert!(PrivacyService::from_str("unknown").is_err());
+
+        Ok(())
+    }
 }
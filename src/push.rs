@@ -1,15 +1,165 @@
 //! Supporting types for the `simctl push` subcommand.
 
 use serde::Serialize;
+#[cfg(feature = "push-bool-flags")]
+use serde::Serializer;
+#[cfg(feature = "push-extra")]
+use std::collections::HashMap;
+use std::path::Path;
 use std::process::Stdio;
 
-use super::{Device, Result, Validate};
+#[cfg(feature = "push-extra")]
+use super::Error;
+use super::{ensure_exists, Device, Execute, Result, Validate};
 
 /// Represents a push notification that can be sent to a device.
 #[derive(Clone, Debug, Default, Serialize)]
 pub struct Push {
     /// Contains the payload of this push notification.
     pub aps: PushPayload,
+
+    /// Contains custom keys that are sent alongside [`Push::aps`] (i.e. at the
+    /// top level of the notification) and that can be read by the app. The
+    /// `aps` key is reserved for [`Push::aps`]: [`Device::push`] rejects
+    /// notifications that use it.
+    #[cfg(feature = "push-extra")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Push {
+    /// Returns a builder that can be used to construct a push notification
+    /// without nesting struct literals.
+    pub fn builder() -> PushBuilder {
+        PushBuilder {
+            push: Push::default(),
+        }
+    }
+}
+
+/// Builder that can be used to construct a push notification.
+#[derive(Clone, Debug, Default)]
+pub struct PushBuilder {
+    push: Push,
+}
+
+impl PushBuilder {
+    fn alert(&mut self) -> &mut PushAlert {
+        self.push.aps.alert.get_or_insert_with(Default::default)
+    }
+
+    /// Updates the title of the alert that is shown to the user.
+    pub fn alert_title(&mut self, title: &str) -> &mut PushBuilder {
+        self.alert().title = Some(title.to_owned());
+        self
+    }
+
+    /// Updates the subtitle of the alert that is shown to the user.
+    pub fn alert_subtitle(&mut self, subtitle: &str) -> &mut PushBuilder {
+        self.alert().subtitle = Some(subtitle.to_owned());
+        self
+    }
+
+    /// Updates the body of the alert that is shown to the user.
+    pub fn alert_body(&mut self, body: &str) -> &mut PushBuilder {
+        self.alert().body = Some(body.to_owned());
+        self
+    }
+
+    /// Updates the number that is shown in the badge on the springboard. Set
+    /// this to 0 to remove an existing badge.
+    pub fn badge(&mut self, badge: usize) -> &mut PushBuilder {
+        self.push.aps.badge = Some(badge);
+        self
+    }
+
+    /// Plays the sound file with the given name from the app's bundle when the
+    /// notification arrives.
+    pub fn sound(&mut self, name: &str) -> &mut PushBuilder {
+        self.push.aps.sound = Some(PushSound {
+            name: name.to_owned(),
+            volume: 1.0,
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Plays the default system sound when the notification arrives.
+    pub fn sound_default(&mut self) -> &mut PushBuilder {
+        self.sound("default")
+    }
+
+    /// Updates the thread id that is used to group related notifications.
+    pub fn thread_id(&mut self, thread_id: &str) -> &mut PushBuilder {
+        self.push.aps.thread_id = Some(thread_id.to_owned());
+        self
+    }
+
+    /// Updates the category that matches with one of the categories registered
+    /// in the app.
+    pub fn category(&mut self, category: &str) -> &mut PushBuilder {
+        self.push.aps.category = Some(category.to_owned());
+        self
+    }
+
+    /// Indicates if content is available. This is used for silent (background)
+    /// notifications.
+    #[cfg(feature = "push-bool-flags")]
+    pub fn content_available(&mut self, content_available: bool) -> &mut PushBuilder {
+        self.push.aps.content_available = content_available;
+        self
+    }
+
+    /// Indicates if content is available. This is used for silent (background)
+    /// notifications.
+    #[cfg(not(feature = "push-bool-flags"))]
+    pub fn content_available(&mut self, content_available: bool) -> &mut PushBuilder {
+        self.push.aps.content_available = Some(content_available as usize);
+        self
+    }
+
+    /// Indicates if this notification should be run through the push
+    /// notification extension of this app to update its content.
+    #[cfg(feature = "push-bool-flags")]
+    pub fn mutable_content(&mut self, mutable_content: bool) -> &mut PushBuilder {
+        self.push.aps.mutable_content = mutable_content;
+        self
+    }
+
+    /// Indicates if this notification should be run through the push
+    /// notification extension of this app to update its content.
+    #[cfg(not(feature = "push-bool-flags"))]
+    pub fn mutable_content(&mut self, mutable_content: bool) -> &mut PushBuilder {
+        self.push.aps.mutable_content = Some(mutable_content as usize);
+        self
+    }
+
+    /// Updates the content ID that is passed to the app when this notification
+    /// is opened.
+    pub fn target_content_id(&mut self, id: &str) -> &mut PushBuilder {
+        self.push.aps.target_content_id = Some(id.to_owned());
+        self
+    }
+
+    /// Adds a custom key with the given value alongside the payload (see
+    /// [`Push::extra`]). The `aps` key is reserved for the payload itself, so
+    /// it is ignored.
+    #[cfg(feature = "push-extra")]
+    pub fn extra<V>(&mut self, key: &str, value: V) -> &mut PushBuilder
+    where
+        V: Into<serde_json::Value>,
+    {
+        if key != "aps" {
+            self.push.extra.insert(key.to_owned(), value.into());
+        }
+
+        self
+    }
+
+    /// Returns the push notification that was built.
+    pub fn build(&self) -> Push {
+        self.push.clone()
+    }
 }
 
 /// Alert that is presented to the user.
@@ -72,6 +222,13 @@ pub struct PushAlert {
 #[derive(Clone, Debug, Default, Serialize)]
 pub struct PushSound {
     /// Enables "critical" push sound.
+    #[cfg(feature = "push-bool-flags")]
+    #[serde(serialize_with = "serialize_flag")]
+    pub critical: bool,
+
+    /// Enables "critical" push sound (should be either 0 or 1). Enable the
+    /// `push-bool-flags` feature to use a `bool` instead.
+    #[cfg(not(feature = "push-bool-flags"))]
     pub critical: usize,
 
     /// Name of the sound file in the app's bundle that will be played.
@@ -99,46 +256,104 @@ pub struct PushPayload {
 
     /// Optional thread id that is used by the OS to group multiple messages
     /// that are related to the same "thread" (e.g. conversation or topic).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "thread-id", skip_serializing_if = "Option::is_none")]
     pub thread_id: Option<String>,
 
     /// Category that matches with one of the categories registered in the app.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub category: Option<String>,
 
+    /// Flag that indicates if content is available.
+    #[cfg(feature = "push-bool-flags")]
+    #[serde(
+        rename = "content-available",
+        serialize_with = "serialize_flag",
+        skip_serializing_if = "is_false"
+    )]
+    pub content_available: bool,
+
     /// Flag that indicates if content is available (should be either 0 or 1).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Enable the `push-bool-flags` feature to use a `bool` instead.
+    #[cfg(not(feature = "push-bool-flags"))]
+    #[serde(rename = "content-available", skip_serializing_if = "Option::is_none")]
     pub content_available: Option<usize>,
 
     /// Flag that indicates if this payload should be run through the push
     /// notification extension of this app to update its content.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg(feature = "push-bool-flags")]
+    #[serde(
+        rename = "mutable-content",
+        serialize_with = "serialize_flag",
+        skip_serializing_if = "is_false"
+    )]
+    pub mutable_content: bool,
+
+    /// Flag that indicates if this payload should be run through the push
+    /// notification extension of this app to update its content (should be
+    /// either 0 or 1). Enable the `push-bool-flags` feature to use a `bool`
+    /// instead.
+    #[cfg(not(feature = "push-bool-flags"))]
+    #[serde(rename = "mutable-content", skip_serializing_if = "Option::is_none")]
     pub mutable_content: Option<usize>,
 
     /// Content ID that is passed to the app when this notification is opened.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "target-content-id", skip_serializing_if = "Option::is_none")]
     pub target_content_id: Option<String>,
 }
 
+/// APNs expects flags to be encoded as either 0 or 1.
+#[cfg(feature = "push-bool-flags")]
+fn serialize_flag<S>(flag: &bool, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_u8(*flag as u8)
+}
+
+#[cfg(feature = "push-bool-flags")]
+fn is_false(flag: &bool) -> bool {
+    !flag
+}
+
 impl Device {
     /// Sends the given push message to this device for an app with the given
     /// bundle ID.
     pub fn push(&self, bundle_id: &str, push: &Push) -> Result<()> {
-        let mut process = self
-            .simctl()
-            .command("push")
-            .arg(&self.udid)
-            .arg(bundle_id)
-            .arg("-")
-            .stdin(Stdio::piped())
-            // .stdout(Stdio::inherit())
-            .spawn()?;
+        #[cfg(feature = "push-extra")]
+        if push.extra.contains_key("aps") {
+            return Err(Error::InvalidArgument(
+                "custom keys of push notifications cannot be named `aps`".to_owned(),
+            ));
+        }
+
+        let mut process = self.simctl().spawn(
+            self.simctl()
+                .command("push")
+                .arg(&self.udid)
+                .arg(bundle_id)
+                .arg("-")
+                .stdin(Stdio::piped()),
+        )?;
 
         if let Some(stdin) = process.stdin.as_mut() {
             serde_json::to_writer(stdin, push)?;
         }
 
-        process.wait_with_output()?.validate()
+        self.simctl().wait_with_output(process)?.validate()
+    }
+
+    /// Sends the push message in the JSON (or .apns) file at the given path to
+    /// this device for an app with the given bundle ID.
+    pub fn push_file(&self, bundle_id: &str, path: &Path) -> Result<()> {
+        ensure_exists(path)?;
+
+        self.simctl()
+            .command("push")
+            .arg(&self.udid)
+            .arg(bundle_id)
+            .arg(path)
+            .execute(self.simctl())?
+            .validate()
     }
 }
 
@@ -163,10 +378,142 @@ mod tests {
                     }),
                     ..Default::default()
                 },
+                ..Default::default()
             },
         )?;
         mock::device()?.shutdown()?;
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_push_file() -> Result<()> {
+        let mut path = Path::new(env!("CARGO_MANIFEST_DIR")).to_path_buf();
+        path.push("tests/Example.apns");
+
+        mock::device()?.boot()?;
+        mock::device()?.push_file("com.apple.mobilecal", &path)?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_builder() -> Result<()> {
+        let push = Push::builder()
+            .alert_body("Hi")
+            .badge(3)
+            .sound_default()
+            .build();
+
+        let expected = Push {
+            aps: PushPayload {
+                alert: Some(PushAlert {
+                    body: Some("Hi".to_owned()),
+                    ..Default::default()
+                }),
+                badge: Some(3),
+                sound: Some(PushSound {
+                    name: "default".to_owned(),
+                    volume: 1.0,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::to_value(&push)?,
+            serde_json::to_value(&expected)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_flags() -> Result<()> {
+        let push = Push::builder().content_available(true).build();
+        let json = serde_json::to_string(&push)?;
+        assert!(json.contains("\"content-available\":1"));
+        assert!(!json.contains("mutable-content"));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "push-bool-flags")]
+    #[test]
+    fn test_push_bool_flags() -> Result<()> {
+        let push = Push {
+            aps: PushPayload {
+                content_available: true,
+                sound: Some(PushSound {
+                    critical: true,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&push)?;
+        assert!(json.contains("\"content-available\":1"));
+        assert!(json.contains("\"critical\":1"));
+        assert!(!json.contains("mutable-content"));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "push-extra")]
+    #[test]
+    fn test_push_extra() -> Result<()> {
+        let push = Push::builder()
+            .alert_title("Hello")
+            .extra("deep_link", "myapp://inbox/42")
+            .build();
+        let json = serde_json::to_value(&push)?;
+        assert_eq!(json["deep_link"], "myapp://inbox/42");
+        assert_eq!(json["aps"]["alert"]["title"], "Hello");
+        assert!(json["aps"].get("deep_link").is_none());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "push-extra")]
+    #[test]
+    fn test_push_extra_aps() -> Result<()> {
+        let push = Push::builder()
+            .alert_title("Hello")
+            .extra("aps", "overwritten")
+            .build();
+        assert!(push.extra.is_empty());
+
+        let simctl = mock::stub("push_extra_aps", "exit 1")?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        let mut push = Push::default();
+        push.extra.insert("aps".to_owned(), "overwritten".into());
+        assert!(matches!(
+            device.push("com.apple.mobilecal", &push),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_keys() -> Result<()> {
+        let push = Push::builder()
+            .thread_id("inbox")
+            .mutable_content(true)
+            .target_content_id("42")
+            .build();
+        let json = serde_json::to_value(&push)?;
+        assert_eq!(json["aps"]["thread-id"], "inbox");
+        assert_eq!(json["aps"]["mutable-content"], 1);
+        assert_eq!(json["aps"]["target-content-id"], "42");
+
+        Ok(())
+    }
 }
@@ -0,0 +1,164 @@
+//! Supporting types for the `simctl runtime` subcommand.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+
+use super::list::RuntimeVersion;
+use super::{Execute, Result, Simctl, Validate};
+
+/// Indicates the state of a runtime disk image.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+pub enum RuntimeImageState {
+    /// Indicates that the runtime is mounted and ready to be used.
+    Ready,
+
+    /// Indicates that the runtime is being deleted.
+    Deleting,
+
+    /// Indicates that the runtime cannot be used (e.g. because its disk image
+    /// failed to mount).
+    Unusable,
+
+    /// Indicates that the runtime is in a state that is not (yet) recognized
+    /// by this library.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Information about a runtime disk image that is installed on this machine.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledRuntime {
+    /// Contains the identifier of this disk image, which can be passed to
+    /// [`Simctl::delete_runtime`].
+    pub identifier: String,
+
+    /// Contains the build of this runtime (e.g. `20E247`).
+    pub build: String,
+
+    /// Contains the version of this runtime (e.g. `16.4`).
+    pub version: String,
+
+    /// Contains the identifier of the runtime that this disk image provides
+    /// (e.g. `com.apple.CoreSimulator.SimRuntime.iOS-16-4`). This is missing
+    /// for disk images that are not (yet) mounted.
+    #[serde(default)]
+    pub runtime_identifier: Option<String>,
+
+    /// Contains the path to the disk image of this runtime.
+    pub path: PathBuf,
+
+    /// Indicates the state of this disk image.
+    pub state: RuntimeImageState,
+
+    /// Contains the size of this disk image in bytes.
+    #[serde(default)]
+    pub size_bytes: u64,
+
+    /// Indicates whether this runtime can be deleted (runtimes that are
+    /// bundled with Xcode cannot).
+    #[serde(default)]
+    pub deletable: bool,
+}
+
+impl Simctl {
+    /// Returns all runtime disk images that are installed on this machine,
+    /// sorted by version and build (and identifier, for disk images of
+    /// different platforms with the same version and build).
+    pub fn runtimes_installed(&self) -> Result<Vec<InstalledRuntime>> {
+        let output = self
+            .command("runtime")
+            .arg("list")
+            .arg("-j")
+            .stdout(Stdio::piped())
+            .execute(self)?;
+
+        let output = output.validate_with_output()?;
+
+        let runtimes: HashMap<String, InstalledRuntime> = serde_json::from_slice(&output.stdout)?;
+
+        let mut runtimes = runtimes.into_values().collect::<Vec<_>>();
+        runtimes.sort_by(|a, b| {
+            RuntimeVersion::parse(&a.version)
+                .cmp(&RuntimeVersion::parse(&b.version))
+                .then_with(|| a.build.cmp(&b.build))
+                .then_with(|| a.identifier.cmp(&b.identifier))
+        });
+
+        Ok(runtimes)
+    }
+
+    /// Deletes the runtime disk image with the given identifier (see
+    /// [`InstalledRuntime::identifier`]).
+    pub fn delete_runtime(&self, identifier: &str) -> Result<()> {
+        self.command("runtime")
+            .arg("delete")
+            .arg(identifier)
+            .execute(self)?
+            .validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    fn test_runtimes_installed() -> Result<()> {
+        let simctl = mock::stub(
+            "runtimes_installed",
+            r#"[ "$1 $2 $3" = "runtime list -j" ] || exit 1
+cat <<'EOF'
+{
+    "C3": { "build": "21A328", "identifier": "C3", "path": "/C3.dmg", "state": "Ready", "version": "17.0" },
+    "A1": { "build": "20E247", "identifier": "A1", "path": "/A1.dmg", "state": "Ready", "version": "16.4" },
+    "D4": { "build": "20E5229e", "identifier": "D4", "path": "/D4.dmg", "state": "Ready", "version": "16.4" },
+    "B2": { "build": "20A360", "identifier": "B2", "path": "/B2.dmg", "state": "Ready", "version": "9.0" }
+}
+EOF"#,
+        )?;
+
+        let identifiers = simctl
+            .runtimes_installed()?
+            .into_iter()
+            .map(|runtime| runtime.identifier)
+            .collect::<Vec<_>>();
+        assert_eq!(identifiers, vec!["B2", "A1", "D4", "C3"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_installed_runtime_deserialize() -> Result<()> {
+        let json = r#"{
+            "5E6D4D7B-0B1F-4A3A-9A55-2F3B9F0C7C1A": {
+                "build": "20E247",
+                "deletable": true,
+                "identifier": "5E6D4D7B-0B1F-4A3A-9A55-2F3B9F0C7C1A",
+                "kind": "Disk Image",
+                "mountPath": "/Library/Developer/CoreSimulator/Volumes/iOS_20E247",
+                "path": "/Library/Developer/CoreSimulator/Images/5E6D4D7B-0B1F-4A3A-9A55-2F3B9F0C7C1A.dmg",
+                "platformIdentifier": "com.apple.platform.iphonesimulator",
+                "runtimeIdentifier": "com.apple.CoreSimulator.SimRuntime.iOS-16-4",
+                "signatureState": "Verified",
+                "sizeBytes": 7157646853,
+                "state": "Ready",
+                "version": "16.4"
+            }
+        }"#;
+
+        let runtimes: HashMap<String, InstalledRuntime> = serde_json::from_str(json)?;
+        let runtime = &runtimes["5E6D4D7B-0B1F-4A3A-9A55-2F3B9F0C7C1A"];
+
+        assert_eq!(runtime.build, "20E247");
+        assert_eq!(runtime.version, "16.4");
+        assert_eq!(runtime.state, RuntimeImageState::Ready);
+        assert_eq!(runtime.size_bytes, 7157646853);
+        assert!(runtime.deletable);
+
+        Ok(())
+    }
+}
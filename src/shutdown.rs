@@ -1,4 +1,13 @@
-use super::{Device, Result, Validate};
+use std::time::Duration;
+
+use super::list::DeviceState;
+#[cfg(feature = "tokio")]
+use super::ExecuteAsync;
+use super::{Device, Execute, Result, Simctl, Validate};
+
+/// Maximum duration that [`Device::shutdown_if_needed`] waits for a device
+/// that is already shutting down.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(60);
 
 impl Device {
     /// Shuts down this device. Returns an error if it isn't booted.
@@ -6,7 +15,44 @@ impl Device {
         self.simctl()
             .command("shutdown")
             .arg(&self.info().udid)
-            .output()?
+            .execute(self.simctl())?
+            .validate()
+    }
+
+    /// Shuts down this device unless it is already shut down. In contrast to
+    /// [`Device::shutdown`], this function does not return an error if the
+    /// device isn't booted. The state of the device is refreshed first, so
+    /// this also works if the device was shut down after this instance was
+    /// retrieved. If the device is still shutting down, this waits (for up to
+    /// a minute) until it has shut down rather than returning an error.
+    pub fn shutdown_if_needed(&self) -> Result<()> {
+        match self.current_state()? {
+            DeviceState::Shutdown => Ok(()),
+            DeviceState::ShuttingDown => {
+                self.wait_for_state(DeviceState::Shutdown, SHUTDOWN_TIMEOUT)
+            }
+            _ => self.shutdown(),
+        }
+    }
+
+    /// Asynchronous variant of [`Device::shutdown`].
+    #[cfg(feature = "tokio")]
+    pub async fn shutdown_async(&self) -> Result<()> {
+        self.simctl()
+            .command_async("shutdown")
+            .arg(&self.info().udid)
+            .execute_async(self.simctl())
+            .await?
+            .validate()
+    }
+}
+
+impl Simctl {
+    /// Shuts down all booted devices.
+    pub fn shutdown_all(&self) -> Result<()> {
+        self.command("shutdown")
+            .arg("all")
+            .execute(self)?
             .validate()
     }
 }
@@ -15,8 +61,9 @@ impl Device {
 mod tests {
     use serial_test::serial;
 
+    use std::path::Path;
+
     use super::*;
-    use crate::list::DeviceState;
     use crate::mock;
 
     #[test]
@@ -30,4 +77,51 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_shutdown_if_needed() -> Result<()> {
+        mock::device()?.shutdown_if_needed()?;
+        assert_eq!(mock::device()?.state, DeviceState::Shutdown);
+
+        mock::device()?.boot()?;
+        mock::device()?.simctl().shutdown_all()?;
+        assert_eq!(mock::device()?.state, DeviceState::Shutdown);
+
+        Ok(())
+    }
+    #[test]
+    fn test_shutdown_if_needed_shutting_down_stub() -> Result<()> {
+        let log = std::env::temp_dir().join("simctl_stub_shutdown_if_needed.log");
+        let marker = std::env::temp_dir().join("simctl_stub_shutdown_if_needed.marker");
+        let _ = std::fs::remove_file(&log);
+        let _ = std::fs::remove_file(&marker);
+
+        // The stub reports the first device (the iPhone SE) as shutting down
+        // once and as shut down afterwards, and refuses to shut it down again.
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/list.json");
+        let script = format!(
+            r#"echo "$1" >> '{log}'
+case "$1" in
+list) if [ -e '{marker}' ]; then
+    cat '{fixture}'
+else
+    touch '{marker}'
+    awk '!done && /"state" : "Shutdown"/ {{ sub("Shutdown", "Shutting Down"); done = 1 }} 1' '{fixture}'
+fi ;;
+*) echo "Unable to shutdown device in current state: Shutting Down" >&2; exit 149 ;;
+esac"#,
+            log = log.display(),
+            marker = marker.display(),
+            fixture = fixture.display()
+        );
+
+        let simctl = mock::stub("shutdown_if_needed", &script)?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        device.shutdown_if_needed()?;
+        assert_eq!(std::fs::read_to_string(&log)?, "list\nlist\n");
+
+        Ok(())
+    }
 }
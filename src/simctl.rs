@@ -1,31 +1,93 @@
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
-use super::{Result, Validate};
+use super::{ensure_exists, Error, Result, Validate};
+
+/// Caches the default developer dir that is reported by `xcode-select`.
+static DEFAULT_DEVELOPER_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Returns the default developer dir that is reported by `xcode-select`. The
+/// lookup happens at most once per process.
+fn default_developer_dir() -> Result<PathBuf> {
+    if let Some(path) = DEFAULT_DEVELOPER_DIR.get() {
+        return Ok(path.clone());
+    }
+
+    let output = Command::new("xcode-select")
+        .arg("--print-path")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()?;
+
+    let output = output.validate_with_output()?;
+    let path = PathBuf::from(String::from_utf8(output.stdout)?.trim());
+
+    Ok(DEFAULT_DEVELOPER_DIR.get_or_init(|| path).clone())
+}
 
 /// Wrapper around the `simctl` utility.
 #[derive(Clone, Debug)]
 pub struct Simctl {
     developer_dir: PathBuf,
+    binary: PathBuf,
+    timeout: Option<Duration>,
+    on_command: Option<CommandHook>,
+    pub(crate) xcode_version: Arc<OnceLock<String>>,
+    pub(crate) subcommands: Arc<OnceLock<Vec<String>>>,
+    pub(crate) supports_send_key: Arc<OnceLock<bool>>,
+}
+
+type CommandHookFn = dyn FnMut(&Command) + Send;
+
+/// Callback that is invoked with every command right before it is spawned.
+#[derive(Clone)]
+struct CommandHook(Arc<Mutex<CommandHookFn>>);
+
+impl fmt::Debug for CommandHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("CommandHook")
+    }
 }
 
 impl Simctl {
     /// Returns a new instance of the Rust wrapper around the `simctl` utility.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the developer dir could not be determined (e.g.
+    /// because Xcode is not installed). Use [`Simctl::try_new`] to handle this
+    /// gracefully.
     pub fn new() -> Simctl {
-        if let Some(developer_dir) = std::env::var_os("DEVELOPER_DIR") {
-            Simctl::with_developer_dir(&Path::new(&developer_dir))
-        } else {
-            let output = Command::new("xcode-select")
-                .arg("--print-path")
-                .stdout(Stdio::piped())
-                .output()
-                .unwrap();
-
-            let output = String::from_utf8(output.stdout).unwrap();
-            let path = Path::new(output.trim());
-
-            Simctl::with_developer_dir(path)
-        }
+        Simctl::try_new().unwrap()
+    }
+
+    /// Returns a new instance of the Rust wrapper around the `simctl` utility,
+    /// or an error if the developer dir could not be determined or does not
+    /// exist. The developer dir is read from the `DEVELOPER_DIR` environment
+    /// variable or otherwise obtained from `xcode-select`.
+    pub fn try_new() -> Result<Simctl> {
+        Simctl::try_new_with(std::env::var_os("DEVELOPER_DIR").as_deref())
+    }
+
+    /// Implementation of [`Simctl::try_new`] that takes the value of the
+    /// `DEVELOPER_DIR` environment variable as an argument, so that it can be
+    /// tested without changing the environment of the entire process.
+    fn try_new_with(developer_dir: Option<&OsStr>) -> Result<Simctl> {
+        let path = match developer_dir {
+            Some(developer_dir) => PathBuf::from(developer_dir),
+            None => default_developer_dir()?,
+        };
+
+        ensure_exists(&path)?;
+
+        Ok(Simctl::with_developer_dir(&path))
     }
 
     /// Returns a new wrapper around the `simctl` utility with the given
@@ -35,35 +97,344 @@ impl Simctl {
     pub fn with_developer_dir(path: &Path) -> Simctl {
         Simctl {
             developer_dir: path.to_path_buf(),
+            binary: path.join("usr/bin/simctl"),
+            timeout: None,
+            on_command: None,
+            xcode_version: Default::default(),
+            subcommands: Default::default(),
+            supports_send_key: Default::default(),
         }
     }
 
+    /// Returns a new wrapper that invokes the given executable rather than the
+    /// `simctl` binary of an installation of Xcode. This is mostly useful in
+    /// tests, to point the wrapper at a stub that emits canned output. The
+    /// developer dir of the returned wrapper is the directory that contains
+    /// the executable.
+    pub fn with_binary(path: &Path) -> Simctl {
+        let developer_dir = path.parent().unwrap_or_else(|| Path::new("/"));
+
+        Simctl {
+            binary: path.to_path_buf(),
+            ..Simctl::with_developer_dir(developer_dir)
+        }
+    }
+
+    /// Returns the developer dir that this wrapper uses (e.g.
+    /// `/Applications/Xcode.app/Contents/Developer`).
+    pub fn developer_dir(&self) -> &Path {
+        &self.developer_dir
+    }
+
+    /// Returns this wrapper with the given timeout. Invocations of the `simctl`
+    /// utility that take longer than this timeout (e.g. because the
+    /// CoreSimulator service is wedged) will be killed and return
+    /// [`Error::Timeout`]. By default, there is no timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Simctl {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Returns this wrapper with the given hook, which is invoked with every
+    /// command right before it is spawned (e.g. to log which invocations of the
+    /// `simctl` utility are made). The hook is shared with clones of this
+    /// wrapper and replaces any previous hook.
+    pub fn on_command<F>(mut self, hook: F) -> Simctl
+    where
+        F: FnMut(&Command) + Send + 'static,
+    {
+        self.on_command = Some(CommandHook(Arc::new(Mutex::new(hook))));
+        self
+    }
+
+    /// Returns the timeout of this wrapper (see [`Simctl::with_timeout`]).
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
     /// Returns a new wrapper around the `simctl` utility with the given Xcode
     /// path. Use this function if Xcode is not installed in the default path or
     /// if you want to distinguish between multiple installations of Xcode (e.g.
     /// stable and beta).
     pub fn with_xcode(path: &Path) -> Simctl {
-        Simctl::with_xcode(&path.join("Contents/Developer"))
+        Simctl::with_developer_dir(&path.join("Contents/Developer"))
     }
 
     /// Returns a new command that will invoke the `simctl` binary with the
     /// given subcommand.
     pub fn command(&self, name: &str) -> Command {
-        let mut command = Command::new(self.developer_dir.join("usr/bin/simctl"));
+        let mut command = Command::new(&self.binary);
         command.arg(name);
+        command.stdin(Stdio::null());
         command.stdout(Stdio::piped());
         command.stderr(Stdio::piped());
         command
     }
 
+    /// Runs the given subcommand with the given arguments and returns its
+    /// output if it completes successfully. This is an escape hatch for
+    /// subcommands (or options) that this crate doesn't wrap (yet). Its output
+    /// is not interpreted in any way, so it may change between Xcode versions.
+    pub fn raw(&self, subcommand: &str, args: &[&str]) -> Result<Output> {
+        self.command(subcommand)
+            .args(args)
+            .execute(self)?
+            .validate_with_output()
+    }
+
+    /// Returns a new asynchronous command that will invoke the `simctl` binary
+    /// with the given subcommand.
+    #[cfg(feature = "tokio")]
+    pub fn command_async(&self, name: &str) -> tokio::process::Command {
+        tokio::process::Command::from(self.command(name))
+    }
+
     /// Opens the Simulator.app that corresponds to this instance of `simctl`
     /// (in case of multiple Xcode installations).
     pub fn open(&self) -> Result<()> {
-        Command::new("open")
-            .arg(self.developer_dir.join("Simulator.app"))
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
+        self.open_command().output()?.validate()
+    }
+
+    /// Opens the Simulator.app that corresponds to this instance of `simctl`
+    /// and focuses the window of the device with the given UDID.
+    pub(crate) fn open_device(&self, udid: &str) -> Result<()> {
+        self.open_command()
+            .arg("--args")
+            .arg("-CurrentDeviceUDID")
+            .arg(udid)
             .output()?
             .validate()
     }
+
+    /// Returns the path of the Simulator.app that corresponds to this instance
+    /// of `simctl` (e.g.
+    /// `/Applications/Xcode.app/Contents/Developer/Applications/Simulator.app`).
+    pub fn simulator_app_path(&self) -> PathBuf {
+        self.developer_dir.join("Applications/Simulator.app")
+    }
+
+    /// Returns a command that opens the Simulator.app. Since `open` is not
+    /// part of `simctl`, it is run directly rather than through
+    /// [`Simctl::on_command`] and [`Simctl::with_timeout`]. Its output is
+    /// captured, so that the error of a failed launch contains its stderr.
+    fn open_command(&self) -> Command {
+        let mut command = Command::new("open");
+        command.arg(self.simulator_app_path());
+        command
+    }
+
+    /// Invokes the command hook (if any) and spawns the given command.
+    pub(crate) fn spawn(&self, command: &mut Command) -> Result<Child> {
+        self.invoke_hook(command);
+        Ok(command.spawn()?)
+    }
+
+    fn invoke_hook(&self, command: &Command) {
+        if let Some(CommandHook(hook)) = &self.on_command {
+            let mut hook = match hook.lock() {
+                Ok(hook) => hook,
+                Err(error) => error.into_inner(),
+            };
+
+            hook(command);
+        }
+    }
+
+    /// Waits for the given child to exit and collects its output. If this
+    /// wrapper has a timeout and the child exceeds it, the child is killed and
+    /// [`Error::Timeout`] is returned.
+    pub(crate) fn wait_with_output(&self, mut child: Child) -> Result<Output> {
+        let timeout = match self.timeout {
+            Some(timeout) => timeout,
+            None => return Ok(child.wait_with_output()?),
+        };
+
+        drop(child.stdin.take());
+
+        let stdout = child.stdout.take().map(read_to_end);
+        let stderr = child.stderr.take().map(read_to_end);
+
+        let deadline = Instant::now() + timeout;
+
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::Timeout);
+            }
+
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        Ok(Output {
+            status,
+            stdout: join(stdout)?,
+            stderr: join(stderr)?,
+        })
+    }
+}
+
+fn read_to_end<R>(mut reader: R) -> JoinHandle<std::io::Result<Vec<u8>>>
+where
+    R: Read + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut buffer = vec![];
+        reader.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    })
+}
+
+fn join(handle: Option<JoinHandle<std::io::Result<Vec<u8>>>>) -> Result<Vec<u8>> {
+    match handle {
+        Some(handle) => Ok(handle.join().unwrap()?),
+        None => Ok(vec![]),
+    }
+}
+
+/// Extension trait that runs a command while honoring the configuration (e.g.
+/// the timeout) of a [`Simctl`] wrapper.
+pub trait Execute {
+    fn execute(&mut self, simctl: &Simctl) -> Result<Output>;
+}
+
+impl Execute for Command {
+    fn execute(&mut self, simctl: &Simctl) -> Result<Output> {
+        let child = simctl.spawn(self)?;
+        simctl.wait_with_output(child)
+    }
+}
+
+/// Asynchronous variant of [`Execute`].
+#[cfg(feature = "tokio")]
+pub trait ExecuteAsync {
+    async fn execute_async(&mut self, simctl: &Simctl) -> Result<Output>;
+}
+
+#[cfg(feature = "tokio")]
+impl ExecuteAsync for tokio::process::Command {
+    async fn execute_async(&mut self, simctl: &Simctl) -> Result<Output> {
+        self.kill_on_drop(true);
+        simctl.invoke_hook(self.as_std());
+
+        match simctl.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, self.output()).await {
+                Ok(output) => Ok(output?),
+                Err(_) => Err(Error::Timeout),
+            },
+            None => Ok(self.output().await?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    fn test_timeout() {
+        let simctl =
+            Simctl::with_developer_dir(Path::new("/")).with_timeout(Duration::from_millis(100));
+
+        let result = mock::slow_command().execute(&simctl);
+        assert!(matches!(result, Err(Error::Timeout)));
+    }
+
+    #[test]
+    fn test_on_command() {
+        let commands = Arc::new(Mutex::new(vec![]));
+
+        let simctl = Simctl::with_developer_dir(Path::new("/nonexistent")).on_command({
+            let commands = commands.clone();
+            move |command| {
+                let mut argv = vec![command.get_program().to_string_lossy().into_owned()];
+                argv.extend(
+                    command
+                        .get_args()
+                        .map(|arg| arg.to_string_lossy().into_owned()),
+                );
+                commands.lock().unwrap().push(argv);
+            }
+        });
+
+        let _ = simctl.list();
+
+        assert_eq!(
+            commands.lock().unwrap().as_slice(),
+            &[vec![
+                "/nonexistent/usr/bin/simctl".to_owned(),
+                "list".to_owned(),
+                "-j".to_owned(),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_with_binary() -> Result<()> {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/list.json");
+        let simctl = mock::stub("list", &format!("cat '{}'", fixture.display()))?;
+        assert_eq!(simctl.developer_dir(), std::env::temp_dir());
+
+        let list = simctl.list()?;
+        assert_eq!(list.devices().len(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_raw() -> Result<()> {
+        let simctl = mock::stub(
+            "raw",
+            "[ \"$#\" = 3 ] && [ \"$1 $2\" = \"spawn booted\" ] && [ \"$3\" = \"defaults read\" ] || exit 1\n\
+             echo '{ AppleLocale = \"en_US\"; }'",
+        )?;
+
+        let output = simctl.raw("spawn", &["booted", "defaults read"])?;
+        assert_eq!(output.stdout, b"{ AppleLocale = \"en_US\"; }\n");
+
+        let error = simctl.raw("spawn", &["booted"]).unwrap_err();
+        assert_eq!(error.exit_code(), Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_xcode() {
+        let simctl = Simctl::with_xcode(Path::new("/Applications/Xcode.app"));
+        assert_eq!(
+            simctl.developer_dir(),
+            Path::new("/Applications/Xcode.app/Contents/Developer")
+        );
+    }
+
+    #[test]
+    fn test_simulator_app_path() {
+        let simctl = Simctl::with_xcode(Path::new("/Applications/Xcode.app"));
+        let path = simctl.simulator_app_path();
+
+        assert!(path.ends_with("Applications/Simulator.app"));
+        assert_eq!(
+            path,
+            Path::new("/Applications/Xcode.app/Contents/Developer/Applications/Simulator.app")
+        );
+    }
+
+    #[test]
+    fn test_try_new_missing_developer_dir() {
+        let result = Simctl::try_new_with(Some(OsStr::new(
+            "/nonexistent/Xcode.app/Contents/Developer",
+        )));
+
+        // This should be reported as an error rather than a panic, so that
+        // tools can degrade gracefully on machines without Xcode.
+        match result {
+            Err(Error::Io(error)) => assert_eq!(error.kind(), std::io::ErrorKind::NotFound),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
 }
@@ -1,9 +1,13 @@
 //! Supporting types for the `simctl status_bar` subcommand.
 
-use super::{Device, Result, Validate};
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+
+use super::{Device, Error, Execute, Result, Validate};
 
 /// Controls the battery state that is shown in the status bar.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub enum BatteryState {
     /// Indicates that the battery is charging.
     Charging,
@@ -17,7 +21,8 @@ pub enum BatteryState {
 }
 
 /// Controls the cellular mode that is shown in the status bar.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub enum CellularMode {
     /// Indicates that this device does not support cellular connectivity.
     NotSupported,
@@ -33,30 +38,43 @@ pub enum CellularMode {
 }
 
 /// Controls the data network that is shown in the status bar.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum DataNetworkType {
+    /// Hides the data network indicator (e.g. to simulate that the device is
+    /// offline). Combine this with [`CellularMode::NotSupported`] and
+    /// [`WifiMode::Failed`] to hide all connectivity.
+    #[serde(rename = "hide")]
+    Hidden,
+
     /// Indicates that the device is connected to a Wi-Fi network.
+    #[serde(rename = "wifi")]
     Wifi,
 
     /// Indicates that the device is connected to a 3G cellular network.
+    #[serde(rename = "3g")]
     Cell3G,
 
     /// Indicates that the device is connected to a 4G cellular network.
+    #[serde(rename = "4g")]
     Cell4G,
 
     /// Indicates that the device is connected to a LTE cellular network.
+    #[serde(rename = "lte")]
     CellLte,
 
     /// Indicates that the device is connected to a LTE-Advanced cellular
     /// network.
+    #[serde(rename = "lte-a")]
     CellLteA,
 
     /// Indicates that the device is connected to a LTE+ cellular network.
+    #[serde(rename = "lte+")]
     CellLtePlus,
 }
 
 /// Controls the Wi-Fi mode that is shown in the status bar.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub enum WifiMode {
     /// Indicates that the device is searching for a Wi-Fi network.
     Searching,
@@ -68,7 +86,108 @@ pub enum WifiMode {
     Active,
 }
 
+/// Current state of the status bar overrides of a device. Fields that are not
+/// overridden are `None`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StatusBarState {
+    /// Contains the time that is shown in the status bar.
+    pub time: Option<String>,
+
+    /// Contains the data network type that is shown in the status bar.
+    pub data_network: Option<DataNetworkType>,
+
+    /// Contains the wifi mode that is shown in the status bar.
+    pub wifi_mode: Option<WifiMode>,
+
+    /// Contains the number of wifi bars that are shown in the status bar.
+    pub wifi_bars: Option<usize>,
+
+    /// Contains the cellular mode that is shown in the status bar.
+    pub cellular_mode: Option<CellularMode>,
+
+    /// Contains the number of cellular bars that are shown in the status bar.
+    pub cellular_bars: Option<usize>,
+
+    /// Contains the operator name that is shown in the status bar.
+    pub operator_name: Option<String>,
+
+    /// Contains the battery state that is shown in the status bar.
+    pub battery_state: Option<BatteryState>,
+
+    /// Contains the battery level that is shown in the status bar.
+    pub battery_level: Option<usize>,
+}
+
+impl StatusBarState {
+    /// Parses the output of `simctl status_bar list`, which consists of lines
+    /// with comma-separated `Key: value` pairs. Enumerations are reported by
+    /// their numeric codes.
+    fn parse(output: &str) -> StatusBarState {
+        let mut state = StatusBarState::default();
+
+        for (key, value) in output
+            .lines()
+            .flat_map(|line| line.split(", "))
+            .filter_map(|pair| {
+                let mut parts = pair.splitn(2, ": ");
+                Some((parts.next()?.trim(), parts.next()?.trim()))
+            })
+        {
+            let code = value.parse::<usize>().ok();
+
+            match key {
+                "Time" => state.time = Some(value.to_owned()),
+                "DataNetworkType" => {
+                    state.data_network = code.and_then(|code| match code {
+                        0 => Some(DataNetworkType::Hidden),
+                        2 => Some(DataNetworkType::Cell3G),
+                        3 => Some(DataNetworkType::Cell4G),
+                        4 => Some(DataNetworkType::CellLte),
+                        5 => Some(DataNetworkType::Wifi),
+                        6 => Some(DataNetworkType::CellLteA),
+                        7 => Some(DataNetworkType::CellLtePlus),
+                        _ => None,
+                    })
+                }
+                "WiFi Mode" => {
+                    state.wifi_mode = code.and_then(|code| match code {
+                        1 => Some(WifiMode::Searching),
+                        2 => Some(WifiMode::Failed),
+                        3 => Some(WifiMode::Active),
+                        _ => None,
+                    })
+                }
+                "WiFi Bars" => state.wifi_bars = code,
+                "Cell Mode" => {
+                    state.cellular_mode = code.and_then(|code| match code {
+                        0 => Some(CellularMode::NotSupported),
+                        1 => Some(CellularMode::Searching),
+                        2 => Some(CellularMode::Failed),
+                        3 => Some(CellularMode::Active),
+                        _ => None,
+                    })
+                }
+                "Cell Bars" => state.cellular_bars = code,
+                "Operator Name" => state.operator_name = Some(value.to_owned()),
+                "Battery State" => {
+                    state.battery_state = code.and_then(|code| match code {
+                        0 => Some(BatteryState::Charging),
+                        1 => Some(BatteryState::Charged),
+                        2 => Some(BatteryState::Discharging),
+                        _ => None,
+                    })
+                }
+                "Battery Level" => state.battery_level = code,
+                _ => {}
+            }
+        }
+
+        state
+    }
+}
+
 /// Wrapper around the `simctl status_bar` subcommand.
+#[derive(Clone, Debug)]
 pub struct StatusBar {
     device: Device,
 }
@@ -81,24 +200,29 @@ impl StatusBar {
             .command("status_bar")
             .arg(&self.device.udid)
             .arg("clear")
-            .output()?
+            .execute(self.device.simctl())?
             .validate()
     }
 
+    /// Returns the current state of the overrides of this status bar.
+    pub fn list(&self) -> Result<StatusBarState> {
+        let output = self
+            .device
+            .simctl()
+            .command("status_bar")
+            .arg(&self.device.udid)
+            .arg("list")
+            .stdout(Stdio::piped())
+            .execute(self.device.simctl())?;
+
+        let output = output.validate_with_output()?;
+
+        Ok(StatusBarState::parse(&String::from_utf8(output.stdout)?))
+    }
+
     /// Creates a new empty override that can be applied to this status bar.
     pub fn empty_override(&self) -> StatusBarOverride {
-        StatusBarOverride {
-            device: self.device.clone(),
-            time: None,
-            data_network: None,
-            wifi_mode: None,
-            wifi_bars: None,
-            cellular_mode: None,
-            cellular_bars: None,
-            operator_name: None,
-            battery_state: None,
-            battery_level: None,
-        }
+        StatusBarOverride::from_state(&self.device, StatusBarState::default())
     }
 }
 
@@ -118,6 +242,25 @@ pub struct StatusBarOverride {
 }
 
 impl StatusBarOverride {
+    /// Creates a new override for the given device that is seeded with the
+    /// given state (e.g. one that was previously read with
+    /// [`StatusBar::list`]). This makes it possible to change some fields of
+    /// the current override while keeping the others.
+    pub fn from_state(device: &Device, state: StatusBarState) -> StatusBarOverride {
+        StatusBarOverride {
+            device: device.clone(),
+            time: state.time,
+            data_network: state.data_network,
+            wifi_mode: state.wifi_mode,
+            wifi_bars: state.wifi_bars,
+            cellular_mode: state.cellular_mode,
+            cellular_bars: state.cellular_bars,
+            operator_name: state.operator_name,
+            battery_state: state.battery_state,
+            battery_level: state.battery_level,
+        }
+    }
+
     /// Updates the time that is shown in the status bar.
     pub fn time(&mut self, time: &str) -> &mut StatusBarOverride {
         self.time = Some(time.to_owned());
@@ -180,8 +323,36 @@ impl StatusBarOverride {
         self
     }
 
-    /// Applies this override to the status bar.
+    /// Returns an error if any of the numeric values of this override are out of
+    /// range.
+    fn validate(&self) -> Result<()> {
+        let ranges = [
+            ("wifi bars", self.wifi_bars, 4),
+            ("cellular bars", self.cellular_bars, 4),
+            ("battery level", self.battery_level, 100),
+        ];
+
+        for &(name, value, max) in ranges.iter() {
+            match value {
+                Some(value) if value > max => {
+                    return Err(Error::InvalidArgument(format!(
+                        "{} must be between 0 and {} (got {})",
+                        name, max, value
+                    )))
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies this override to the status bar. Returns an error without
+    /// invoking the CLI if the number of wifi or cellular bars exceeds 4, or if
+    /// the battery level exceeds 100.
     pub fn apply(&self) -> Result<()> {
+        self.validate()?;
+
         let mut command = self.device.simctl().command("status_bar");
 
         command.arg(&self.device.udid).arg("override");
@@ -192,6 +363,7 @@ impl StatusBarOverride {
 
         if let Some(network) = self.data_network.as_ref() {
             command.arg("--dataNetwork").arg(match network {
+                DataNetworkType::Hidden => "hide",
                 DataNetworkType::Wifi => "wifi",
                 DataNetworkType::Cell3G => "3g",
                 DataNetworkType::Cell4G => "4g",
@@ -242,7 +414,7 @@ impl StatusBarOverride {
             command.arg("--batteryLevel").arg(level.to_string());
         }
 
-        command.output()?.validate()
+        command.execute(self.device.simctl())?.validate()
     }
 }
 
@@ -282,4 +454,138 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_status_bar_override_from_state() -> Result<()> {
+        mock::device()?.boot()?;
+        mock::device()?
+            .status_bar()
+            .empty_override()
+            .time("00:00")
+            .battery_state(BatteryState::Discharging)
+            .battery_level(42)
+            .apply()?;
+
+        let state = mock::device()?.status_bar().list()?;
+        StatusBarOverride::from_state(&mock::device()?, state)
+            .time("12:34")
+            .apply()?;
+
+        let state = mock::device()?.status_bar().list()?;
+        assert_eq!(state.time.as_deref(), Some("12:34"));
+        assert_eq!(state.battery_state, Some(BatteryState::Discharging));
+        assert_eq!(state.battery_level, Some(42));
+
+        mock::device()?.status_bar().clear()?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_status_bar_list() -> Result<()> {
+        mock::device()?.boot()?;
+        mock::device()?
+            .status_bar()
+            .empty_override()
+            .time("00:00")
+            .battery_state(BatteryState::Discharging)
+            .battery_level(42)
+            .apply()?;
+
+        let state = mock::device()?.status_bar().list()?;
+        assert_eq!(state.time.as_deref(), Some("00:00"));
+        assert_eq!(state.battery_level, Some(42));
+
+        mock::device()?.status_bar().clear()?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_status_bar_disconnected() -> Result<()> {
+        mock::device()?.boot()?;
+        mock::device()?
+            .status_bar()
+            .empty_override()
+            .data_network(DataNetworkType::Hidden)
+            .wifi_mode(WifiMode::Failed)
+            .cellular_mode(CellularMode::NotSupported)
+            .apply()?;
+
+        let state = mock::device()?.status_bar().list()?;
+        assert_eq!(state.data_network, Some(DataNetworkType::Hidden));
+        assert_eq!(state.wifi_mode, Some(WifiMode::Failed));
+        assert_eq!(state.cellular_mode, Some(CellularMode::NotSupported));
+
+        mock::device()?.status_bar().clear()?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_status_bar_validate() -> Result<()> {
+        let status_bar = mock::fixture_device("iPhone SE (2nd generation)")?.status_bar();
+
+        assert!(matches!(
+            status_bar.empty_override().wifi_bars(9).apply(),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        assert!(matches!(
+            status_bar.empty_override().battery_level(150).apply(),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_status_bar_state_parse() {
+        let state = StatusBarState::parse(
+            "Current Status Bar Overrides:\n\
+             =============================\n\
+             Time: 9:41\n\
+             WiFi Mode: 3, WiFi Bars: 3\n\
+             Cell Mode: 3, Cell Bars: 4\n\
+             Operator Name: Babel\n\
+             Battery State: 2, Battery Level: 42, Show Not Charging: 0\n",
+        );
+
+        assert_eq!(state.time.as_deref(), Some("9:41"));
+        assert_eq!(state.wifi_mode, Some(WifiMode::Active));
+        assert_eq!(state.wifi_bars, Some(3));
+        assert_eq!(state.cellular_mode, Some(CellularMode::Active));
+        assert_eq!(state.cellular_bars, Some(4));
+        assert_eq!(state.operator_name.as_deref(), Some("Babel"));
+        assert_eq!(state.battery_state, Some(BatteryState::Discharging));
+        assert_eq!(state.battery_level, Some(42));
+    }
+
+    #[test]
+    fn test_serialize() -> Result<()> {
+        assert_eq!(
+            serde_json::to_string(&DataNetworkType::CellLtePlus)?,
+            "\"lte+\""
+        );
+        assert_eq!(
+            serde_json::to_string(&CellularMode::NotSupported)?,
+            "\"notSupported\""
+        );
+        assert_eq!(
+            serde_json::from_str::<BatteryState>("\"discharging\"")?,
+            BatteryState::Discharging
+        );
+        assert_eq!(
+            serde_json::from_str::<WifiMode>("\"active\"")?,
+            WifiMode::Active
+        );
+
+        Ok(())
+    }
 }
@@ -1,4 +1,27 @@
-use super::{Device, Result, Validate};
+use super::list_apps::ApplicationType;
+use super::{Device, Execute, Result, Validate};
+
+/// Parses the bundle IDs of running apps from the output of `launchctl list`.
+/// Each line contains the PID (or `-` if not running), the last exit status
+/// and the label of a service. Apps are represented by services with labels
+/// like `UIKitApplication:<bundle id>[<suffix>]`.
+fn parse_running_apps(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let pid = columns.next()?;
+            let label = columns.nth(1)?;
+
+            match pid {
+                "-" => None,
+                _ => label.strip_prefix("UIKitApplication:"),
+            }
+        })
+        .filter_map(|label| label.split('[').next())
+        .map(|bundle_id| bundle_id.to_owned())
+        .collect()
+}
 
 impl Device {
     /// Terminates a running application with the given bundle ID on this
@@ -8,9 +31,31 @@ impl Device {
             .command("terminate")
             .arg(&self.udid)
             .arg(bundle_id)
-            .output()?
+            .execute(self.simctl())?
             .validate()
     }
+
+    /// Returns the bundle IDs of all apps (including system apps) that have a
+    /// running process on this device, by looking for their UIKit application
+    /// services in the output of `launchctl list`.
+    pub fn running_apps(&self) -> Result<Vec<String>> {
+        let output = self.launchctl(&["list"])?;
+
+        Ok(parse_running_apps(&String::from_utf8(output.stdout)?))
+    }
+
+    /// Terminates all running apps that were installed by the user (see
+    /// [`crate::list_apps::ApplicationType::User`]) on this device. System
+    /// apps (e.g. Safari) are not terminated.
+    pub fn terminate_all(&self) -> Result<()> {
+        let running = self.running_apps()?;
+
+        self.list_apps()?
+            .iter()
+            .filter(|app| app.application_type == ApplicationType::User)
+            .filter(|app| running.contains(&app.bundle_id))
+            .try_for_each(|app| self.terminate(&app.bundle_id))
+    }
 }
 
 #[cfg(test)]
@@ -53,4 +98,65 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_terminate_all() -> Result<()> {
+        mock::device()?.boot()?;
+
+        mock::device()?.open_url_and_wait(
+            "https://www.glacyr.com/",
+            "com.apple.mobilesafari",
+            std::time::Duration::from_secs(30),
+        )?;
+        mock::device()?.terminate_all()?;
+
+        // Safari is a system app, so it should still be running.
+        assert!(mock::device()?
+            .running_apps()?
+            .iter()
+            .any(|app| app == "com.apple.mobilesafari"));
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_terminate_all_stub() -> Result<()> {
+        let log = std::env::temp_dir().join("simctl_stub_terminate_all.log");
+        let _ = std::fs::remove_file(&log);
+
+        let script = format!(
+            r#"case "$1" in
+spawn) printf '%s\n' 'PID	Status	Label' '123	0	UIKitApplication:com.apple.mobilesafari[a1b2][rb-legacy]' '456	0	UIKitApplication:com.glacyr.simctl.Example[c3d4][rb-legacy]' '-	0	UIKitApplication:com.glacyr.simctl.Other[e5f6][rb-legacy]' ;;
+listapps) cat <<'EOF'
+{{
+    "com.apple.mobilesafari" = {{ ApplicationType = System; CFBundleIdentifier = "com.apple.mobilesafari"; Path = "/Safari.app"; }};
+    "com.glacyr.simctl.Example" = {{ ApplicationType = User; CFBundleIdentifier = "com.glacyr.simctl.Example"; Path = "/Example.app"; }};
+    "com.glacyr.simctl.Other" = {{ ApplicationType = User; CFBundleIdentifier = "com.glacyr.simctl.Other"; Path = "/Other.app"; }};
+}}
+EOF
+;;
+terminate) echo "$3" >> '{}' ;;
+esac"#,
+            log.display()
+        );
+
+        let simctl = mock::stub("terminate_all", &script)?;
+        let device = mock::fixture_device_with(simctl, "iPhone SE (2nd generation)")?;
+
+        assert_eq!(
+            device.running_apps()?,
+            vec!["com.apple.mobilesafari", "com.glacyr.simctl.Example"]
+        );
+
+        device.terminate_all()?;
+        assert_eq!(
+            std::fs::read_to_string(&log)?,
+            "com.glacyr.simctl.Example\n"
+        );
+
+        Ok(())
+    }
 }
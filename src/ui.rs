@@ -1,8 +1,9 @@
 //! Supporting types for the `simctl ui` subcommand.
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::process::Stdio;
 
-use super::{Device, Result, Validate};
+use super::{Device, Error, Execute, Result, Validate};
 
 /// Determines the appearance mode of the UI.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -18,6 +19,165 @@ pub enum Appearance {
     Custom(String),
 }
 
+impl Appearance {
+    fn as_str(&self) -> &str {
+        match self {
+            Appearance::Light => "light",
+            Appearance::Dark => "dark",
+            Appearance::Custom(appearance) => appearance,
+        }
+    }
+
+    fn parse(appearance: String) -> Appearance {
+        match appearance.as_str() {
+            "light" => Appearance::Light,
+            "dark" => Appearance::Dark,
+            _ => Appearance::Custom(appearance),
+        }
+    }
+}
+
+impl Serialize for Appearance {
+    /// Serializes this appearance into the same token that is used by the CLI
+    /// (e.g. `dark`).
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Appearance {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Appearance::parse)
+    }
+}
+
+/// Determines the preferred content size category (i.e. Dynamic Type) of the
+/// UI.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ContentSize {
+    /// Indicates an extra small content size.
+    ExtraSmall,
+
+    /// Indicates a small content size.
+    Small,
+
+    /// Indicates a medium content size.
+    Medium,
+
+    /// Indicates a large content size (i.e. the default).
+    Large,
+
+    /// Indicates an extra large content size.
+    ExtraLarge,
+
+    /// Indicates an extra extra large content size.
+    ExtraExtraLarge,
+
+    /// Indicates an extra extra extra large content size. This is the largest
+    /// size that is available without enabling larger accessibility sizes.
+    ExtraExtraExtraLarge,
+
+    /// Indicates a medium accessibility content size.
+    AccessibilityMedium,
+
+    /// Indicates a large accessibility content size.
+    AccessibilityLarge,
+
+    /// Indicates an extra large accessibility content size.
+    AccessibilityExtraLarge,
+
+    /// Indicates an extra extra large accessibility content size.
+    AccessibilityExtraExtraLarge,
+
+    /// Indicates an extra extra extra large accessibility content size. This is
+    /// the largest size that is available.
+    AccessibilityExtraExtraExtraLarge,
+
+    /// Increments the current content size by one category. This can only be
+    /// used with [`UI::set_content_size`].
+    Increment,
+
+    /// Decrements the current content size by one category. This can only be
+    /// used with [`UI::set_content_size`].
+    Decrement,
+
+    /// This is returned when the CLI reports a content size that is not (yet)
+    /// recognized by this library.
+    Custom(String),
+}
+
+impl ContentSize {
+    fn as_str(&self) -> &str {
+        match self {
+            ContentSize::ExtraSmall => "extra-small",
+            ContentSize::Small => "small",
+            ContentSize::Medium => "medium",
+            ContentSize::Large => "large",
+            ContentSize::ExtraLarge => "extra-large",
+            ContentSize::ExtraExtraLarge => "extra-extra-large",
+            ContentSize::ExtraExtraExtraLarge => "extra-extra-extra-large",
+            ContentSize::AccessibilityMedium => "accessibility-medium",
+            ContentSize::AccessibilityLarge => "accessibility-large",
+            ContentSize::AccessibilityExtraLarge => "accessibility-extra-large",
+            ContentSize::AccessibilityExtraExtraLarge => "accessibility-extra-extra-large",
+            ContentSize::AccessibilityExtraExtraExtraLarge => {
+                "accessibility-extra-extra-extra-large"
+            }
+            ContentSize::Increment => "increment",
+            ContentSize::Decrement => "decrement",
+            ContentSize::Custom(content_size) => content_size,
+        }
+    }
+
+    fn parse(content_size: String) -> ContentSize {
+        match content_size.as_str() {
+            "extra-small" => ContentSize::ExtraSmall,
+            "small" => ContentSize::Small,
+            "medium" => ContentSize::Medium,
+            "large" => ContentSize::Large,
+            "extra-large" => ContentSize::ExtraLarge,
+            "extra-extra-large" => ContentSize::ExtraExtraLarge,
+            "extra-extra-extra-large" => ContentSize::ExtraExtraExtraLarge,
+            "accessibility-medium" => ContentSize::AccessibilityMedium,
+            "accessibility-large" => ContentSize::AccessibilityLarge,
+            "accessibility-extra-large" => ContentSize::AccessibilityExtraLarge,
+            "accessibility-extra-extra-large" => ContentSize::AccessibilityExtraExtraLarge,
+            "accessibility-extra-extra-extra-large" => {
+                ContentSize::AccessibilityExtraExtraExtraLarge
+            }
+            "increment" => ContentSize::Increment,
+            "decrement" => ContentSize::Decrement,
+            _ => ContentSize::Custom(content_size),
+        }
+    }
+}
+
+impl Serialize for ContentSize {
+    /// Serializes this content size into the same token that is used by the
+    /// CLI (e.g. `extra-large`).
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ContentSize {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(ContentSize::parse)
+    }
+}
+
 /// Wrapper around the `simctl ui` subcommand.
 #[derive(Clone, Debug)]
 pub struct UI {
@@ -45,35 +205,84 @@ impl UI {
             .arg(&self.device.udid)
             .arg("appearance")
             .stdout(Stdio::piped())
-            .output()?;
+            .execute(self.device.simctl())?;
 
         let output = output.validate_with_output()?;
 
         let appearance = String::from_utf8(output.stdout)?.trim().to_owned();
-        Ok(match appearance.as_str() {
-            "light" => Appearance::Light,
-            "dark" => Appearance::Dark,
-            _ => Appearance::Custom(appearance),
-        })
+        Ok(Appearance::parse(appearance))
     }
 
-    /// Sets the current appearance of the UI of this device.
+    /// Returns a boolean that indicates if this device supports changing its
+    /// appearance. This is not supported by watchOS and tvOS devices.
+    pub fn supports_appearance(&self) -> bool {
+        let runtime = &self.device.runtime_identifier;
+        !runtime.contains("watchOS") && !runtime.contains("tvOS")
+    }
+
+    /// Sets the current appearance of the UI of this device. Returns
+    /// [`Error::Unsupported`] without invoking the CLI if the device doesn't
+    /// support changing its appearance (see [`UI::supports_appearance`]).
     pub fn set_appearance(&self, appearance: Appearance) -> Result<()> {
-        let appearance = match &appearance {
-            Appearance::Light => "light",
-            Appearance::Dark => "dark",
-            Appearance::Custom(appearance) => appearance,
-        };
+        if !self.supports_appearance() {
+            return Err(Error::Unsupported(format!(
+                "{} ({}) does not support changing its appearance",
+                self.device.name, self.device.runtime_identifier
+            )));
+        }
 
         self.device
             .simctl()
             .command("ui")
             .arg(&self.device.udid)
             .arg("appearance")
-            .arg(appearance)
-            .output()?
+            .arg(appearance.as_str())
+            .execute(self.device.simctl())?
+            .validate()
+    }
+
+    /// Returns the current content size category of the UI of this device.
+    pub fn content_size(&self) -> Result<ContentSize> {
+        let output = self
+            .device
+            .simctl()
+            .command("ui")
+            .arg(&self.device.udid)
+            .arg("content_size")
+            .stdout(Stdio::piped())
+            .execute(self.device.simctl())?;
+
+        let output = output.validate_with_output()?;
+
+        let content_size = String::from_utf8(output.stdout)?.trim().to_owned();
+        Ok(ContentSize::parse(content_size))
+    }
+
+    /// Sets the current content size category of the UI of this device.
+    pub fn set_content_size(&self, content_size: ContentSize) -> Result<()> {
+        self.device
+            .simctl()
+            .command("ui")
+            .arg(&self.device.udid)
+            .arg("content_size")
+            .arg(content_size.as_str())
+            .execute(self.device.simctl())?
             .validate()
     }
+
+    /// Increases the content size category of the UI of this device by one
+    /// step. The CLI clamps the result, so calling this on a device that
+    /// already uses the largest accessibility category has no effect.
+    pub fn increase_content_size(&self) -> Result<()> {
+        self.set_content_size(ContentSize::Increment)
+    }
+
+    /// Decreases the content size category of the UI of this device by one
+    /// step. The CLI clamps the result, so calling this on a device that
+    /// already uses the smallest category has no effect.
+    pub fn decrease_content_size(&self) -> Result<()> {
+        self.set_content_size(ContentSize::Decrement)
+    }
 }
 
 #[cfg(test)]
@@ -98,4 +307,73 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_content_size() -> Result<()> {
+        mock::device()?.boot()?;
+
+        mock::device()?.ui().set_content_size(ContentSize::Large)?;
+        assert_eq!(mock::device()?.ui().content_size()?, ContentSize::Large);
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_increase_content_size() -> Result<()> {
+        mock::device()?.boot()?;
+
+        mock::device()?.ui().set_content_size(ContentSize::Large)?;
+        mock::device()?.ui().increase_content_size()?;
+        mock::device()?.ui().increase_content_size()?;
+        assert_eq!(
+            mock::device()?.ui().content_size()?,
+            ContentSize::ExtraExtraLarge
+        );
+
+        mock::device()?.ui().decrease_content_size()?;
+        assert_eq!(
+            mock::device()?.ui().content_size()?,
+            ContentSize::ExtraLarge
+        );
+
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize() -> Result<()> {
+        assert_eq!(serde_json::to_string(&Appearance::Dark)?, "\"dark\"");
+        assert_eq!(
+            serde_json::from_str::<Appearance>("\"dark\"")?,
+            Appearance::Dark
+        );
+        assert_eq!(
+            serde_json::to_string(&ContentSize::AccessibilityLarge)?,
+            "\"accessibility-large\""
+        );
+        assert_eq!(
+            serde_json::from_str::<ContentSize>("\"huge\"")?,
+            ContentSize::Custom("huge".to_owned())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_appearance_unsupported() -> Result<()> {
+        let device = mock::fixture_device("Apple Watch Series 6 - 44mm")?;
+
+        assert!(!device.ui().supports_appearance());
+        assert!(matches!(
+            device.ui().set_appearance(Appearance::Dark),
+            Err(Error::Unsupported(_))
+        ));
+
+        Ok(())
+    }
 }
@@ -1,4 +1,4 @@
-use super::{Device, Result, Validate};
+use super::{Device, Execute, Result, Validate};
 
 impl Device {
     /// Uninstalls an app with the given bundle ID from this device.
@@ -7,9 +7,24 @@ impl Device {
             .command("uninstall")
             .arg(&self.udid)
             .arg(&bundle_id)
-            .output()?
+            .execute(self.simctl())?
             .validate()
     }
+
+    /// Uninstalls an app with the given bundle ID from this device if it is
+    /// installed. In contrast to [`Device::uninstall`], this function does not
+    /// return an error if there is no such app.
+    pub fn uninstall_if_installed(&self, bundle_id: &str) -> Result<()> {
+        let installed = self
+            .list_apps()?
+            .iter()
+            .any(|app| app.bundle_id == bundle_id);
+
+        match installed {
+            true => self.uninstall(bundle_id),
+            false => Ok(()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -33,4 +48,14 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_uninstall_if_installed() -> Result<()> {
+        mock::device()?.boot()?;
+        mock::device()?.uninstall_if_installed("com.glacyr.simctl.NeverInstalled")?;
+        mock::device()?.shutdown()?;
+
+        Ok(())
+    }
 }
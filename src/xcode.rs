@@ -0,0 +1,174 @@
+//! Supporting types for detecting the capabilities of an Xcode installation.
+
+use std::process::{Command, Stdio};
+
+use super::{Error, Result, Simctl, Validate};
+
+/// Refers to a feature of `simctl` that is not available in every version of
+/// Xcode.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Feature {
+    /// Sending simulated push notifications (`simctl push`). Requires Xcode
+    /// 11.4.
+    Push,
+
+    /// Granting and revoking permissions (`simctl privacy`). Requires Xcode
+    /// 11.4.
+    Privacy,
+
+    /// Changing the appearance of the UI (`simctl ui appearance`). Requires
+    /// Xcode 11.4.
+    Appearance,
+
+    /// Overriding the status bar (`simctl status_bar`). Requires Xcode 11.4.
+    StatusBar,
+
+    /// Simulating the location of a device (`simctl location`). Requires Xcode
+    /// 14.0.
+    Location,
+
+    /// Changing the preferred content size of the UI (`simctl ui
+    /// content_size`). Requires Xcode 15.0.
+    ContentSize,
+}
+
+impl Feature {
+    fn minimum_version(&self) -> (u32, u32) {
+        match self {
+            Feature::Push | Feature::Privacy | Feature::Appearance | Feature::StatusBar => (11, 4),
+            Feature::Location => (14, 0),
+            Feature::ContentSize => (15, 0),
+        }
+    }
+}
+
+/// Parses the major and minor components of a version string (e.g. `12.5.1`).
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let mut components = version.split('.');
+    let major = components.next()?.parse().ok()?;
+    let minor = match components.next() {
+        Some(minor) => minor.parse().ok()?,
+        None => 0,
+    };
+
+    Some((major, minor))
+}
+
+impl Simctl {
+    /// Returns the version of Xcode that this wrapper uses (e.g. `12.5.1`). The
+    /// version is obtained by running `xcodebuild -version` once and is cached
+    /// afterwards (also for clones of this wrapper). Since `xcodebuild` is not
+    /// part of `simctl`, it doesn't go through [`Simctl::on_command`] and isn't
+    /// subject to [`Simctl::with_timeout`].
+    pub fn xcode_version(&self) -> Result<String> {
+        if let Some(version) = self.xcode_version.get() {
+            return Ok(version.clone());
+        }
+
+        let output = Command::new(self.developer_dir().join("usr/bin/xcodebuild"))
+            .arg("-version")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()?;
+
+        let output = output.validate_with_output()?;
+        let stdout = String::from_utf8(output.stdout)?;
+
+        let version = stdout
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("Xcode "))
+            .map(|version| version.trim().to_owned())
+            .ok_or_else(|| {
+                Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("unexpected output of xcodebuild -version: {}", stdout),
+                ))
+            })?;
+
+        let _ = self.xcode_version.set(version.clone());
+
+        Ok(version)
+    }
+
+    /// Returns a boolean that indicates if the version of Xcode that this
+    /// wrapper uses supports the given feature.
+    pub fn supports(&self, feature: Feature) -> Result<bool> {
+        let version = self.xcode_version()?;
+
+        match parse_version(&version) {
+            Some(version) => Ok(version >= feature.minimum_version()),
+            None => Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unrecognized Xcode version: {}", version),
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Returns a developer dir in the temporary directory of which
+    /// `usr/bin/xcodebuild` is a shell script with the given body.
+    fn developer_dir(name: &str, script: &str) -> Result<PathBuf> {
+        let root = std::env::temp_dir().join(format!("simctl_xcode_{}", name));
+        std::fs::create_dir_all(root.join("usr/bin"))?;
+
+        let path = root.join("usr/bin/xcodebuild");
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", script))?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+
+        Ok(root)
+    }
+
+    #[test]
+    fn test_xcode_version() -> Result<()> {
+        let version = Simctl::new().xcode_version()?;
+        assert!(parse_version(&version).is_some());
+        assert!(Simctl::new().supports(Feature::Push)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_xcode_version_stub() -> Result<()> {
+        let path = developer_dir(
+            "version",
+            "[ \"$1\" = \"-version\" ] || exit 1\n\
+             echo \"Xcode 14.3.1\"\n\
+             echo \"Build version 14E300c\"",
+        )?;
+        let simctl = Simctl::with_developer_dir(&path);
+
+        assert_eq!(simctl.xcode_version()?, "14.3.1");
+        assert!(simctl.supports(Feature::Location)?);
+        assert!(!simctl.supports(Feature::ContentSize)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_xcode_version_unexpected_output() -> Result<()> {
+        let path = developer_dir("unexpected", "echo \"Command line tools\"")?;
+        let simctl = Simctl::with_developer_dir(&path);
+
+        assert!(matches!(simctl.xcode_version(), Err(Error::Io(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("12.5.1"), Some((12, 5)));
+        assert_eq!(parse_version("15"), Some((15, 0)));
+        assert_eq!(parse_version("beta"), None);
+        assert!(parse_version("11.4").unwrap() >= Feature::Push.minimum_version());
+        assert!(parse_version("13.4").unwrap() < Feature::Location.minimum_version());
+    }
+}